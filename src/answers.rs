@@ -0,0 +1,244 @@
+use crate::input_reader::{read_input, InputError, InputSource};
+use crate::solver::{Answer, Puzzle};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use toml::{Table, Value};
+
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+const PARTS: [(&str, u8); 2] = [("part1", 1), ("part2", 2)];
+
+// Expected answers keyed by input name and part, e.g.
+//
+// [input_01]
+// part1 = 3246517
+// part2 = 29379307
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    expected: BTreeMap<(String, u8), Answer>,
+}
+
+impl Answers {
+    pub fn expected(&self, input: &str, part: u8) -> Option<&Answer> {
+        self.expected.get(&(input.to_string(), part))
+    }
+
+    pub fn insert(&mut self, input: &str, part: u8, answer: Answer) {
+        self.expected.insert((input.to_string(), part), answer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expected.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Input(InputError),
+    Invalid(String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Input(error) => write!(f, "{}", error),
+            AnswersError::Invalid(message) => write!(f, "invalid answers file: {}", message),
+        }
+    }
+}
+
+impl Error for AnswersError {}
+
+pub fn load_answers(source: &InputSource) -> Result<Answers, AnswersError> {
+    let text = read_input(source).map_err(AnswersError::Input)?;
+    parse_answers(&text)
+}
+
+pub fn parse_answers(text: &str) -> Result<Answers, AnswersError> {
+    let table = text.parse::<Table>()
+        .map_err(|e| AnswersError::Invalid(e.message().to_string()))?;
+
+    let mut answers = Answers::default();
+    for (input, parts) in table {
+        let parts = parts.as_table()
+            .ok_or(AnswersError::Invalid(format!("{}: expected a table with part1 and/or part2", input)))?;
+
+        for (key, value) in parts {
+            let part = PARTS.iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, part)| *part)
+                .ok_or(AnswersError::Invalid(format!("{}: unknown key '{}', expected part1 or part2", input, key)))?;
+
+            answers.insert(&input, part, parse_answer(value).ok_or(AnswersError::Invalid(
+                format!("{}.{}: expected an integer or a string", input, key)
+            ))?);
+        }
+    }
+
+    Ok(answers)
+}
+
+fn parse_answer(value: &Value) -> Option<Answer> {
+    match value {
+        Value::Integer(number) => Some(Answer::Number(*number)),
+        Value::String(text) => Some(Answer::Text(text.clone())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Wrong { expected: Answer, actual: Answer },
+    MissingInput,
+    InvalidInput(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub day: u8,
+    pub part: u8,
+    pub outcome: Outcome,
+}
+
+pub fn verify<'a>(puzzles: impl IntoIterator<Item = &'a Puzzle>, answers: &Answers) -> Vec<Verification> {
+    puzzles.into_iter()
+        .flat_map(|puzzle| match read_input(&InputSource::Named(puzzle.input.to_string())) {
+            Ok(input) => verify_puzzle(puzzle, &input, answers),
+            Err(InputError::NotFound { .. }) => with_outcome(puzzle, answers, Outcome::MissingInput),
+            Err(error) => with_outcome(puzzle, answers, Outcome::InvalidInput(error.to_string())),
+        })
+        .collect()
+}
+
+pub fn verify_puzzle(puzzle: &Puzzle, input: &str, answers: &Answers) -> Vec<Verification> {
+    if expected_parts(puzzle, answers).is_empty() {
+        return vec![];
+    }
+
+    let parsed_input = match puzzle.solver.parse(input) {
+        Ok(parsed_input) => parsed_input,
+        Err(error) => return with_outcome(puzzle, answers, Outcome::InvalidInput(error.to_string())),
+    };
+
+    expected_parts(puzzle, answers)
+        .into_iter()
+        .map(|(part, expected)| {
+            let actual = puzzle.solve(parsed_input.as_ref(), part);
+            let outcome = match actual {
+                Answer::Error(error) => Outcome::InvalidInput(error),
+                _ if actual == *expected => Outcome::Correct,
+                _ => Outcome::Wrong { expected: expected.clone(), actual },
+            };
+            Verification { day: puzzle.day, part, outcome }
+        })
+        .collect()
+}
+
+fn expected_parts<'a>(puzzle: &Puzzle, answers: &'a Answers) -> Vec<(u8, &'a Answer)> {
+    PARTS.iter()
+        .filter(|&&(_, part)| puzzle.has_part(part))
+        .filter_map(|&(_, part)| answers.expected(puzzle.input, part).map(|answer| (part, answer)))
+        .collect()
+}
+
+fn with_outcome(puzzle: &Puzzle, answers: &Answers, outcome: Outcome) -> Vec<Verification> {
+    expected_parts(puzzle, answers)
+        .into_iter()
+        .map(|(part, _)| Verification { day: puzzle.day, part, outcome: outcome.clone() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::find_puzzle;
+    use indoc::indoc;
+
+    #[test]
+    fn it_parses_the_answers() {
+        let answers = parse_answers(indoc! {r#"
+        [input_01]
+        part1 = 11
+        part2 = 31
+
+        [input_23]
+        part2 = "co,de,ka,ta"
+        "#}).unwrap();
+
+        assert_eq!(Some(&Answer::Number(11)), answers.expected("input_01", 1));
+        assert_eq!(Some(&Answer::Number(31)), answers.expected("input_01", 2));
+        assert_eq!(None, answers.expected("input_23", 1));
+        assert_eq!(Some(&Answer::Text("co,de,ka,ta".to_string())), answers.expected("input_23", 2));
+    }
+
+    #[test]
+    fn it_rejects_invalid_answers() {
+        assert_eq!(
+            "invalid answers file: input_01: unknown key 'part3', expected part1 or part2",
+            parse_answers("[input_01]\npart3 = 1").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "invalid answers file: input_01.part1: expected an integer or a string",
+            parse_answers("[input_01]\npart1 = 1.5").unwrap_err().to_string()
+        );
+        assert!(parse_answers("input_01 = 3").is_err());
+    }
+
+    #[test]
+    fn it_verifies_a_puzzle_against_the_expected_answers() {
+        let puzzle = find_puzzle(1).unwrap();
+        let mut answers = Answers::default();
+        answers.insert("input_01", 1, Answer::Number(11));
+        answers.insert("input_01", 2, Answer::Number(30));
+
+        assert_eq!(
+            vec![
+                Verification { day: 1, part: 1, outcome: Outcome::Correct },
+                Verification {
+                    day: 1,
+                    part: 2,
+                    outcome: Outcome::Wrong { expected: Answer::Number(30), actual: Answer::Number(31) },
+                },
+            ],
+            verify_puzzle(puzzle, "3   4\n4   3\n2   5\n1   3\n3   9\n3   3", &answers)
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_inputs_for_every_expected_part() {
+        let puzzle = find_puzzle(1).unwrap();
+        let mut answers = Answers::default();
+        answers.insert("input_01", 2, Answer::Number(31));
+
+        assert_eq!(
+            vec![Verification {
+                day: 1,
+                part: 2,
+                outcome: Outcome::InvalidInput("day 1: line 1, column 5: expected a location id".to_string()),
+            }],
+            verify_puzzle(puzzle, "3   x", &answers)
+        );
+    }
+
+    #[test]
+    fn it_skips_puzzles_without_expected_answers() {
+        let mut answers = Answers::default();
+        answers.insert("input_that_does_not_exist", 1, Answer::Number(1));
+
+        assert!(verify_puzzle(find_puzzle(2).unwrap(), "", &answers).is_empty());
+    }
+
+    #[test]
+    fn it_skips_parts_a_day_does_not_have() {
+        let mut answers = Answers::default();
+        answers.insert("input_25", 1, Answer::Number(0));
+        answers.insert("input_25", 2, Answer::Text("Deliver the Chronicle".to_string()));
+
+        assert_eq!(
+            vec![Verification { day: 25, part: 1, outcome: Outcome::Correct }],
+            verify_puzzle(find_puzzle(25).unwrap(), "#####\n.....\n.....", &answers)
+        );
+    }
+}
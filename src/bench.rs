@@ -0,0 +1,268 @@
+use crate::parse_error::ParseError;
+use crate::solver::Puzzle;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchOptions {
+    pub warmup: usize,
+    pub iterations: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self { warmup: 1, iterations: 5 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Stage {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "parse" => Some(Stage::Parse),
+            "part1" => Some(Stage::Part1),
+            "part2" => Some(Stage::Part2),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part1 => write!(f, "part1"),
+            Stage::Part2 => write!(f, "part2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub day: u8,
+    pub stage: Stage,
+    pub iterations: usize,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+    pub max_ns: u64,
+}
+
+impl Timing {
+    pub fn from_samples(day: u8, stage: Stage, samples: &[Duration]) -> Self {
+        let mut nanos: Vec<u64> = samples.iter().map(|s| s.as_nanos() as u64).collect();
+        nanos.sort_unstable();
+
+        let iterations = nanos.len();
+        let total: u64 = nanos.iter().sum();
+        Self {
+            day,
+            stage,
+            iterations,
+            min_ns: nanos.first().copied().unwrap_or(0),
+            median_ns: nanos.get(iterations / 2).copied().unwrap_or(0),
+            mean_ns: total.checked_div(iterations as u64).unwrap_or(0),
+            max_ns: nanos.last().copied().unwrap_or(0),
+        }
+    }
+
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos(self.mean_ns)
+    }
+}
+
+pub fn measure<T>(options: &BenchOptions, mut run: impl FnMut() -> T) -> Vec<Duration> {
+    for _ in 0..options.warmup {
+        black_box(run());
+    }
+
+    (0..options.iterations)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(run());
+            start.elapsed()
+        })
+        .collect()
+}
+
+pub fn bench_puzzle(puzzle: &Puzzle, input: &str, options: &BenchOptions) -> Result<Vec<Timing>, ParseError> {
+    let parsed_input = puzzle.solver.parse(input)?;
+
+    let parse = measure(options, || puzzle.solver.parse(input));
+    let part1 = measure(options, || puzzle.solve(parsed_input.as_ref(), 1));
+
+    let mut timings = vec![
+        Timing::from_samples(puzzle.day, Stage::Parse, &parse),
+        Timing::from_samples(puzzle.day, Stage::Part1, &part1),
+    ];
+    if puzzle.has_part(2) {
+        let part2 = measure(options, || puzzle.solve(parsed_input.as_ref(), 2));
+        timings.push(Timing::from_samples(puzzle.day, Stage::Part2, &part2));
+    }
+    Ok(timings)
+}
+
+const CSV_HEADER: &str = "day,stage,iterations,min_ns,median_ns,mean_ns,max_ns";
+
+pub fn to_csv(timings: &[Timing]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    for t in timings {
+        csv.push_str(&format!(
+            "\n{},{},{},{},{},{},{}",
+            t.day, t.stage, t.iterations, t.min_ns, t.median_ns, t.mean_ns, t.max_ns
+        ));
+    }
+    csv
+}
+
+pub fn from_csv(csv: &str) -> Result<Vec<Timing>, String> {
+    let mut lines = csv.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header == CSV_HEADER => {}
+        _ => return Err(format!("line 1: expected the header '{}'", CSV_HEADER)),
+    }
+
+    lines
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| parse_csv_row(line).ok_or(format!("line {}: invalid timing '{}'", index + 1, line)))
+        .collect()
+}
+
+fn parse_csv_row(line: &str) -> Option<Timing> {
+    let fields: Vec<_> = line.split(',').collect();
+    if fields.len() != 7 {
+        return None;
+    }
+
+    Some(Timing {
+        day: fields[0].parse().ok()?,
+        stage: Stage::from_name(fields[1])?,
+        iterations: fields[2].parse().ok()?,
+        min_ns: fields[3].parse().ok()?,
+        median_ns: fields[4].parse().ok()?,
+        mean_ns: fields[5].parse().ok()?,
+        max_ns: fields[6].parse().ok()?,
+    })
+}
+
+pub fn to_json(timings: &[Timing]) -> String {
+    serde_json::to_string_pretty(timings).expect("timings are always serializable")
+}
+
+pub fn from_json(json: &str) -> Result<Vec<Timing>, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub day: u8,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub current: Duration,
+    pub change: f64,
+    pub regression: bool,
+}
+
+// A stage regresses when its mean time grows by more than `threshold` (0.1 is 10%) over the baseline
+pub fn compare(current: &[Timing], baseline: &[Timing], threshold: f64) -> Vec<Comparison> {
+    current.iter()
+        .filter_map(|timing| {
+            let previous = baseline.iter().find(|b| b.day == timing.day && b.stage == timing.stage)?;
+            let change = if previous.mean_ns == 0 {
+                0.0
+            } else {
+                timing.mean_ns as f64 / previous.mean_ns as f64 - 1.0
+            };
+
+            Some(Comparison {
+                day: timing.day,
+                stage: timing.stage,
+                baseline: previous.mean(),
+                current: timing.mean(),
+                change,
+                regression: change > threshold,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::find_puzzle;
+
+    fn timing(day: u8, stage: Stage, mean_ns: u64) -> Timing {
+        Timing { day, stage, iterations: 3, min_ns: mean_ns - 1, median_ns: mean_ns, mean_ns, max_ns: mean_ns + 1 }
+    }
+
+    #[test]
+    fn it_summarises_the_samples() {
+        let samples = [30, 10, 20, 40].map(Duration::from_nanos);
+
+        assert_eq!(
+            Timing { day: 4, stage: Stage::Part1, iterations: 4, min_ns: 10, median_ns: 30, mean_ns: 25, max_ns: 40 },
+            Timing::from_samples(4, Stage::Part1, &samples)
+        );
+    }
+
+    #[test]
+    fn it_runs_warmup_and_iterations() {
+        let mut runs = 0;
+        let samples = measure(&BenchOptions { warmup: 2, iterations: 3 }, || runs += 1);
+
+        assert_eq!(5, runs);
+        assert_eq!(3, samples.len());
+    }
+
+    #[test]
+    fn it_times_every_stage_of_a_puzzle() {
+        let puzzle = find_puzzle(1).unwrap();
+        let options = BenchOptions { warmup: 0, iterations: 2 };
+        let timings = bench_puzzle(puzzle, "3   4\n4   3\n2   5", &options).unwrap();
+
+        assert_eq!(
+            vec![(1, Stage::Parse, 2), (1, Stage::Part1, 2), (1, Stage::Part2, 2)],
+            timings.iter().map(|t| (t.day, t.stage, t.iterations)).collect::<Vec<_>>()
+        );
+        assert!(bench_puzzle(puzzle, "3   x", &options).is_err());
+
+        let timings = bench_puzzle(find_puzzle(25).unwrap(), "#####\n.....\n.....", &options).unwrap();
+        assert_eq!(vec![Stage::Parse, Stage::Part1], timings.iter().map(|t| t.stage).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn it_writes_and_reads_reports() {
+        let timings = vec![timing(1, Stage::Parse, 100), timing(16, Stage::Part2, 5000)];
+
+        assert_eq!(
+            "day,stage,iterations,min_ns,median_ns,mean_ns,max_ns\n1,parse,3,99,100,100,101\n16,part2,3,4999,5000,5000,5001",
+            to_csv(&timings)
+        );
+        assert_eq!(Ok(timings.clone()), from_csv(&to_csv(&timings)));
+        assert_eq!(Ok(timings.clone()), from_json(&to_json(&timings)));
+        assert!(from_csv("day,stage\n1,parse").is_err());
+    }
+
+    #[test]
+    fn it_flags_regressions_against_the_baseline() {
+        let baseline = vec![timing(1, Stage::Parse, 100), timing(1, Stage::Part1, 100)];
+        let current = vec![timing(1, Stage::Parse, 105), timing(1, Stage::Part1, 150), timing(2, Stage::Parse, 10)];
+
+        let comparisons = compare(&current, &baseline, 0.1);
+
+        assert_eq!(
+            vec![(Stage::Parse, false), (Stage::Part1, true)],
+            comparisons.iter().map(|c| (c.stage, c.regression)).collect::<Vec<_>>()
+        );
+        assert!((comparisons[1].change - 0.5).abs() < 1e-9);
+    }
+}
@@ -1,8 +1,74 @@
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use itertools::Itertools;
+
+pub struct Day1;
+
+impl Solver for Day1 {
+    type Input = (Vec<usize>, Vec<usize>);
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(&self, (c1, c2): &Self::Input) -> Answer {
+        total_distance(c1.clone(), c2.clone()).into()
+    }
+
+    fn part2(&self, (c1, c2): &Self::Input) -> Answer {
+        similarity_score(c1, c2).into()
+    }
+}
+
+pub fn calculate_similarity_score(input: &str) -> Result<usize, ParseError> {
+    let (c1, c2) = parse_input(input)?;
+    Ok(similarity_score(&c1, &c2))
+}
+
+fn similarity_score(c1: &[usize], c2: &[usize]) -> usize {
+    let frequencies = c2.iter().counts();
+    c1.iter()
+        .map(|v1| v1 * frequencies.get(v1).unwrap_or(&0))
+        .sum()
+}
+
+pub fn calculate_total_distance(input: &str) -> Result<usize, ParseError> {
+    let (c1, c2) = parse_input(input)?;
+    Ok(total_distance(c1, c2))
+}
+
+fn total_distance(mut c1: Vec<usize>, mut c2: Vec<usize>) -> usize {
+    c1.sort();
+    c2.sort();
+    c1.iter()
+        .zip(c2)
+        .map(|(v1, v2)| v1.abs_diff(v2))
+        .sum()
+}
+
+pub fn parse_line(line: &Line) -> Result<(usize, usize), ParseError> {
+    let (left, right) = line.split_once("   ")?;
+    Ok((
+        line.parse(left, "a location id")?,
+        line.parse(right, "a location id")?
+    ))
+}
+
+pub fn parse_input(input: &str) -> Result<(Vec<usize>, Vec<usize>), ParseError> {
+    lines(1, input)
+        .map(|line| parse_line(&line))
+        .fold_ok((vec![], vec![]), |(mut c1, mut c2), (v1, v2)| {
+            c1.push(v1);
+            c2.push(v2);
+            (c1, c2)
+        })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use itertools::Itertools;
-    use crate::input_reader::*;
 
     #[test]
     fn it_parses_the_input() {
@@ -14,15 +80,21 @@ mod tests {
         3   9
         3   3
         "};
-        let (c1, c2) = parse_input(&input);
+        let (c1, c2) = parse_input(input).unwrap();
         assert_eq!(vec![3, 4, 2, 1, 3, 3], c1);
         assert_eq!(vec![4, 3, 5, 3, 9, 3], c2);
     }
 
+    #[test]
+    fn it_reports_where_the_input_is_invalid() {
+        assert_eq!(Err(ParseError::new(1, 2, 4, "'   '")), parse_input("3   4\n4 3"));
+        assert_eq!(Err(ParseError::new(1, 1, 1, "a location id")), parse_input("-3   4"));
+    }
+
     #[test]
     fn it_parses_a_line() {
-        let line = "3   4";
-        assert_eq!((3, 4), parse_line(line))
+        let line = Line::new(1, 0, "3   4");
+        assert_eq!(Ok((3, 4)), parse_line(&line))
     }
 
     #[test]
@@ -34,13 +106,13 @@ mod tests {
         1   3
         3   9
         3   3"};
-        assert_eq!(11, calculate_total_distance(input))
+        assert_eq!(Ok(11), calculate_total_distance(input))
     }
 
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_01");
-        assert_eq!(3246517, calculate_total_distance(input))
+        let input = &personal_input!("input_01");
+        assert_eq!(Ok(3246517), calculate_total_distance(input))
     }
 
     #[test]
@@ -52,47 +124,12 @@ mod tests {
         1   3
         3   9
         3   3"};
-        assert_eq!(31, calculate_similarity_score(input))
+        assert_eq!(Ok(31), calculate_similarity_score(input))
     }
 
     #[test]
     fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_01");
-        assert_eq!(29379307, calculate_similarity_score(input))
-    }
-
-    fn calculate_similarity_score(input: &str) -> usize {
-        let (c1, c2) = parse_input(input);
-        let frequencies = c2.iter().counts();
-        c1.iter()
-            .map(|v1| v1 * frequencies.get(v1).unwrap_or(&0))
-            .sum()
-    }
-
-    fn calculate_total_distance(input: &str) -> usize {
-        let (mut c1, mut c2) = parse_input(input);
-        c1.sort();
-        c2.sort();
-        c1.iter()
-            .zip(c2)
-            .map(|(v1, v2)| v1.abs_diff(v2))
-            .sum()
-    }
-
-    fn parse_line(line: &str) -> (usize, usize) {
-        line.split("   ")
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect_tuple()
-            .unwrap()
-    }
-
-    fn parse_input(input: &str) -> (Vec<usize>, Vec<usize>) {
-        read_lines(input).iter()
-            .map(|line| parse_line(line))
-            .fold((vec![], vec![]), |(mut c1, mut c2), (v1, v2)| {
-                c1.push(v1);
-                c2.push(v2);
-                (c1, c2)
-            })
+        let input = &personal_input!("input_01");
+        assert_eq!(Ok(29379307), calculate_similarity_score(input))
     }
-}
\ No newline at end of file
+}
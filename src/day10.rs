@@ -1,57 +1,44 @@
-use crate::input_reader::read_lines;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse_error::ParseError;
+use crate::solver::{Answer, Solver};
 use std::collections::HashSet;
 
-#[derive(Debug, Copy, Clone, Hash, PartialOrd, Eq, PartialEq)]
-struct Position(isize, isize);
-
-impl Position {
-    fn neighbours_position(&self, dimension: isize) -> Vec<Position> {
-        [
-            (0, 1),
-            (0, -1),
-            (1, 0),
-            (-1, 0)
-        ].iter()
-            .filter_map(|(dx, dy)| {
-                if self.0 + dx < 0 || self.0 + dx >= dimension {
-                    return None;
-                }
+pub struct Day10;
 
-                if self.1 + dy < 0 || self.1 + dy >= dimension {
-                    return None;
-                }
+impl Solver for Day10 {
+    type Input = Map;
 
-                Some(Position(self.0 + dx, self.1 + dy))
-            }).collect()
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        Map::build_from(input)
+    }
+
+    fn part1(&self, map: &Self::Input) -> Answer {
+        map.find_nines().into()
+    }
+
+    fn part2(&self, map: &Self::Input) -> Answer {
+        map.find_all_paths().into()
     }
 }
 
-struct Map {
-    map: Vec<Vec<char>>,
-    zeros: Vec<Position>,
-    dimension: isize,
+pub struct Map {
+    map: Grid<Option<usize>>,
+    zeros: Vec<Point>,
 }
 
 impl Map {
-    fn build_from(input: &str) -> Self {
-        let mut zeros = vec![];
-        let mut map = vec![];
-
-        for (y, line) in read_lines(input).iter().enumerate() {
-            map.push(line.chars().collect::<Vec<_>>());
-            for (x, c) in line.chars().enumerate() {
-                if c == '0' {
-                    zeros.push(Position(x as isize, y as isize))
-                }
-            }
-        }
-
-        let dimension = map.len() as isize;
-
-        Map { zeros, map, dimension }
+    pub fn build_from(input: &str) -> Result<Self, ParseError> {
+        let map = Grid::parse(10, input, "a height or '.'", |c| match c {
+            '.' => Some(None),
+            _ => c.to_digit(10).map(|v| Some(v as usize)),
+        })?;
+        let zeros = map.find_all(&Some(0)).collect();
+
+        Ok(Map { zeros, map })
     }
 
-    fn find_nines(&self) -> usize {
+    pub fn find_nines(&self) -> usize {
         self.zeros
             .iter()
             .map(|z| {
@@ -62,15 +49,15 @@ impl Map {
             .sum()
     }
 
-    fn find_nines_from_position(&self, position: &Position, nines: &mut HashSet<Position>) {
-        if let Some(9) = self.value_at_position(&position) {
-            nines.insert(position.clone());
+    fn find_nines_from_position(&self, position: &Point, nines: &mut HashSet<Point>) {
+        if let Some(9) = self.value_at_position(position) {
+            nines.insert(*position);
             return;
         }
 
-        let current = self.value_at_position(&position).unwrap();
+        let current = self.value_at_position(position).unwrap();
 
-        for np in position.neighbours_position(self.dimension) {
+        for np in self.map.neighbours(*position) {
             let n = self.value_at_position(&np);
             if n.is_none() {
                 continue;
@@ -84,28 +71,24 @@ impl Map {
         }
     }
 
-    fn find_all_paths(&self) -> usize {
+    pub fn find_all_paths(&self) -> usize {
         self.zeros
             .iter()
             .map(|z| self.find_all_paths_from_position(z))
             .sum()
     }
 
-    fn find_all_paths_from_position(&self, position: &Position) -> usize {
-        if let Some(9) = self.value_at_position(&position) {
+    fn find_all_paths_from_position(&self, position: &Point) -> usize {
+        if let Some(9) = self.value_at_position(position) {
             return 1;
         }
 
-        let current = self.value_at_position(&position).unwrap();
+        let current = self.value_at_position(position).unwrap();
 
-        position.neighbours_position(self.dimension)
-            .iter()
+        self.map.neighbours(*position)
             .filter_map(|np| {
-                let n = self.value_at_position(&np);
-                if n.is_none() {
-                    return None;
-                }
-                if n.unwrap() != current + 1 {
+                let n = self.value_at_position(&np)?;
+                if n != current + 1 {
                     return None;
                 }
 
@@ -113,20 +96,29 @@ impl Map {
             }).sum()
     }
 
-    fn value_at_position(&self, Position(x, y): &Position) -> Option<usize> {
-        self.map[*y as usize][*x as usize].to_digit(10).map(|v| v as usize)
+    fn value_at_position(&self, position: &Point) -> Option<usize> {
+        self.map[*position]
     }
 }
 
+pub fn find_score(input: &str) -> Result<usize, ParseError> {
+    Ok(Map::build_from(input)?.find_nines())
+}
+
+pub fn find_all_paths_score(input: &str) -> Result<usize, ParseError> {
+    Ok(Map::build_from(input)?.find_all_paths())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day10::Map;
-    use crate::input_reader::read_input_file;
+    use crate::day10::{find_all_paths_score, find_score};
+    use crate::parse_error::ParseError;
+    use crate::personal_input;
     use indoc::indoc;
 
     #[test]
     fn it_finds_score() {
-        assert_eq!(2, find_score(indoc! {"
+        assert_eq!(Ok(2), find_score(indoc! {"
         ...0...
         ...1...
         ...2...
@@ -135,7 +127,7 @@ mod tests {
         8.....8
         9.....9"}));
 
-        assert_eq!(36, find_score(indoc! {"
+        assert_eq!(Ok(36), find_score(indoc! {"
         89010123
         78121874
         87430965
@@ -148,7 +140,7 @@ mod tests {
 
     #[test]
     fn it_finds_all_paths_score() {
-        assert_eq!(3, find_all_paths_score(indoc! {"
+        assert_eq!(Ok(3), find_all_paths_score(indoc! {"
         .....0.
         ..4321.
         ..5..2.
@@ -157,7 +149,7 @@ mod tests {
         ..8765.
         ..9...."}));
 
-        assert_eq!(81, find_all_paths_score(indoc! {"
+        assert_eq!(Ok(81), find_all_paths_score(indoc! {"
         89010123
         78121874
         87430965
@@ -169,18 +161,15 @@ mod tests {
     }
 
     #[test]
-    fn it_solves_both_puzzles() {
-        let input = &read_input_file("input_10");
-
-        assert_eq!(489, find_score(input));
-        assert_eq!(1086, find_all_paths_score(input))
+    fn it_reports_invalid_heights() {
+        assert_eq!(Err(ParseError::new(10, 2, 2, "a height or '.'")), find_score("012\n3x4\n567"));
     }
 
-    fn find_score(input: &str) -> usize {
-        Map::build_from(input).find_nines()
-    }
+    #[test]
+    fn it_solves_both_puzzles() {
+        let input = &personal_input!("input_10");
 
-    fn find_all_paths_score(input: &str) -> usize {
-        Map::build_from(input).find_all_paths()
+        assert_eq!(Ok(489), find_score(input));
+        assert_eq!(Ok(1086), find_all_paths_score(input))
     }
 }
\ No newline at end of file
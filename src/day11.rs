@@ -1,9 +1,75 @@
+use crate::parse_error::{empty_input, lines, ParseError};
+use crate::solver::{Answer, Solver};
+use memoize::memoize;
+use num::Integer;
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    type Input = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_stones(input)
+    }
+
+    fn part1(&self, stones: &Self::Input) -> Answer {
+        count_stones(stones, 25).into()
+    }
+
+    fn part2(&self, stones: &Self::Input) -> Answer {
+        count_stones(stones, 75).into()
+    }
+}
+
+pub fn count_stones_after_blinking(input: &str, times: usize) -> Result<usize, ParseError> {
+    Ok(count_stones(&parse_stones(input)?, times))
+}
+
+fn count_stones(stones: &[String], times: usize) -> usize {
+    stones.iter()
+        .map(|stone| blink(stone.clone(), times))
+        .sum()
+}
+
+pub fn parse_stones(input: &str) -> Result<Vec<String>, ParseError> {
+    let line = lines(11, input)
+        .next()
+        .ok_or(empty_input(11, "a stone"))?;
+
+    line.text.split(" ")
+        .map(|stone| line.parse::<usize>(stone, "a stone").map(|n| n.to_string()))
+        .collect()
+}
+
+#[memoize]
+fn blink(stone: String, counter: usize) -> usize {
+    if counter == 0 {
+        return 1;
+    }
+
+    if stone == "0" {
+        return blink("1".to_string(), counter - 1);
+    }
+
+    let number = stone.parse::<usize>().unwrap();
+
+    if stone.len().is_even() {
+        let len = stone.len();
+        let exp = (len / 2) as u32;
+        let left = number / (10_usize.pow(exp));
+        let right = number % (10_usize.pow(exp));
+        return blink(left.to_string(), counter - 1) +
+            blink(right.to_string(), counter - 1);
+    }
+
+    blink((number * 2024).to_string(), counter - 1)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use memoize::memoize;
-    use num::Integer;
 
     #[test]
     fn it_counts_stones_after_blinking_25_times() {
@@ -11,47 +77,20 @@ mod tests {
             125 17
         "};
 
-        assert_eq!(55312, count_stones_after_blinking(input, 25))
+        assert_eq!(Ok(55312), count_stones_after_blinking(input, 25))
     }
 
     #[test]
-    fn it_solves_puzzles() {
-        let input = &read_input_file("input_11");
-
-        assert_eq!(202019, count_stones_after_blinking(input, 25));
-        assert_eq!(239321955280205, count_stones_after_blinking(input, 75))
+    fn it_reports_invalid_stones() {
+        assert_eq!(Err(ParseError::new(11, 1, 5, "a stone")), parse_stones("125 -17"));
+        assert_eq!(Err(ParseError::new(11, 1, 1, "a stone")), parse_stones(""));
     }
 
-    fn count_stones_after_blinking(input: &str, times: usize) -> usize {
-        let lines = read_lines(input);
-        let parsed_input = lines[0].split(" ").collect::<Vec<&str>>();
-
-        parsed_input.iter()
-            .map(|stone| blink(stone.to_string(), times))
-            .sum()
-    }
-
-    #[memoize]
-    fn blink(stone: String, counter: usize) -> usize {
-        if counter == 0 {
-            return 1;
-        }
-
-        if stone == "0" {
-            return blink("1".to_string(), counter - 1);
-        }
-
-        let number = stone.parse::<usize>().unwrap();
-
-        if stone.len().is_even() {
-            let len = stone.len();
-            let exp = (len / 2) as u32;
-            let left = number / (10_usize.pow(exp));
-            let right = number % (10_usize.pow(exp));
-            return blink(left.to_string(), counter - 1) +
-                blink(right.to_string(), counter - 1);
-        }
+    #[test]
+    fn it_solves_puzzles() {
+        let input = &personal_input!("input_11");
 
-        blink((number * 2024).to_string(), counter - 1)
+        assert_eq!(Ok(202019), count_stones_after_blinking(input, 25));
+        assert_eq!(Ok(239321955280205), count_stones_after_blinking(input, 75))
     }
-}
\ No newline at end of file
+}
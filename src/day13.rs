@@ -1,25 +1,129 @@
+use crate::geometry::Point;
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use regex::Regex;
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    type Input = Vec<Machine>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_machines(input)
+    }
+
+    fn part1(&self, machines: &Self::Input) -> Answer {
+        total_tokens(machines, false).into()
+    }
+
+    fn part2(&self, machines: &Self::Input) -> Answer {
+        total_tokens(machines, true).into()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Machine {
+    pub button_a: Point,
+    pub button_b: Point,
+    pub prize: Point,
+}
+
+fn calculate_tokens_with_math(button_a: Point, button_b: Point, prize: Point) -> Option<usize> {
+    let den = button_a.y * button_b.x - button_a.x * button_b.y;
+    if den == 0 {
+        return None;
+    }
+
+    let b = (prize.x * button_a.y - button_a.x * prize.y) / den;
+    let a = (prize.y - button_b.y * b) / button_a.y;
+    if button_a * a + button_b * b != prize {
+        return None;
+    }
+
+    Some((3 * a + b) as usize)
+}
+
+pub fn calculate_total_tokens(input: &str, extend: bool) -> Result<usize, ParseError> {
+    Ok(total_tokens(&parse_machines(input)?, extend))
+}
+
+fn total_tokens(machines: &[Machine], extend: bool) -> usize {
+    machines.iter()
+        .filter_map(|m| calculate_tokens_with_math(m.button_a, m.button_b, extend_prize(m.prize, extend)))
+        .sum()
+}
+
+fn extend_prize(prize: Point, extend: bool) -> Point {
+    if !extend {
+        return prize;
+    }
+
+    prize + Point::new(10000000000000, 10000000000000)
+}
+
+pub fn parse_machines(input: &str) -> Result<Vec<Machine>, ParseError> {
+    let mut machines = vec![];
+
+    let mut lines = lines(13, input).filter(|l| !l.text.is_empty());
+    while let Some(line) = lines.next() {
+        let button_a = parse_button(&line, "Button A: ")?;
+        let line = next_line(&mut lines, &line, "Button B: ")?;
+        let button_b = parse_button(&line, "Button B: ")?;
+        let line = next_line(&mut lines, &line, "Prize: ")?;
+        let prize = parse_prize(&line)?;
+
+        machines.push(Machine { button_a, button_b, prize });
+    }
+
+    Ok(machines)
+}
+
+fn next_line<'a>(lines: &mut impl Iterator<Item = Line<'a>>, previous: &Line, prefix: &str) -> Result<Line<'a>, ParseError> {
+    lines.next()
+        .ok_or(ParseError::new(13, previous.number + 1, 1, format!("'{}'", prefix)))
+}
+
+fn parse_button(line: &Line, prefix: &str) -> Result<Point, ParseError> {
+    let button_pattern = Regex::new(r"^X\+(\d+), Y\+(\d+)$").unwrap();
+    parse_tuple(line, line.strip_prefix(prefix)?, button_pattern, "'X+<n>, Y+<n>'")
+}
+
+fn parse_prize(line: &Line) -> Result<Point, ParseError> {
+    let prize_pattern = Regex::new(r"^X=(\d+), Y=(\d+)$").unwrap();
+    parse_tuple(line, line.strip_prefix("Prize: ")?, prize_pattern, "'X=<n>, Y=<n>'")
+}
+
+fn parse_tuple(line: &Line, raw_tuple: &str, pattern: Regex, expected: &str) -> Result<Point, ParseError> {
+    let (_, [first, second]) = pattern
+        .captures(raw_tuple)
+        .ok_or(line.error_at(raw_tuple, expected))?
+        .extract();
+
+    Ok(Point::new(line.parse::<isize>(first, "a number")?, line.parse::<isize>(second, "a number")?))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use regex::Regex;
 
     #[test]
     fn it_calculates_tokens() {
-        let button_a = (94, 34);
-        let button_b = (22, 67);
-        let prize = (8400, 5400);
+        let button_a = Point::new(94, 34);
+        let button_b = Point::new(22, 67);
+        let prize = Point::new(8400, 5400);
 
         assert_eq!(Some(280), calculate_tokens_with_math(button_a, button_b, prize));
 
-        let button_a = (26, 66);
-        let button_b = (67, 21);
-        let prize = (12748, 12176);
+        let button_a = Point::new(26, 66);
+        let button_b = Point::new(67, 21);
+        let prize = Point::new(12748, 12176);
         assert_eq!(None, calculate_tokens_with_math(button_a, button_b, prize));
 
-        let button_a = (17, 86);
-        let button_b = (84, 37);
-        let prize = (7870, 6450);
+        let button_a = Point::new(17, 86);
+        let button_b = Point::new(84, 37);
+        let prize = Point::new(7870, 6450);
         assert_eq!(Some(200), calculate_tokens_with_math(button_a, button_b, prize));
     }
 
@@ -43,83 +147,35 @@ mod tests {
         Prize: X=18641, Y=10279
         "};
 
-        assert_eq!(480, calculate_total_tokens(input, false));
+        assert_eq!(Ok(480), calculate_total_tokens(input, false));
     }
 
     #[test]
-    fn it_solves_puzzles() {
-        let input = &read_input_file("input_13");
-
-        assert_eq!(38839, calculate_total_tokens(input, false));
-        assert_eq!(75200131617108, calculate_total_tokens(input, true)); // too high
-    }
-
-    fn calculate_tokens_with_math(button_a: (isize, isize), button_b: (isize, isize), prize: (isize, isize)) -> Option<usize> {
-        let den = button_a.1 * button_b.0 - button_a.0 * button_b.1;
-        if den == 0 {
-            return None;
-        }
-
-        let b = (prize.0 * button_a.1 - button_a.0 * prize.1) / den;
-        let a = (prize.1 - button_b.1 * b) / button_a.1;
-        if a * button_a.0 + b * button_b.0 != prize.0 {
-            return None;
-        }
-
-        if a * button_a.1 + b * button_b.1 != prize.1 {
-            return None;
-        }
-
-        Some((3 * a + b) as usize)
-    }
-
-    fn calculate_total_tokens(input: &str, extend: bool) -> usize {
-        let lines = read_lines(input);
-
-        let mut total_tokens = 0;
-        let mut index = 0;
-        while index < lines.len() {
-            if lines[index] == "" {
-                index += 1;
-                continue;
-            }
-
+    fn it_reports_invalid_machines() {
+        let input = indoc! {"
+        Button A: X+94, Y+34
+        Button B: X+22, Y+67
+        Prize: X=8400, Y=5400
 
-            let button_a = parse_button(&lines[index]);
-            let button_b = parse_button(&lines[index + 1]);
-            let prize = parse_prize(&lines[index + 2], extend);
+        Button A: X+26, Y+66
+        Button C: X+67, Y+21
+        "};
+        assert_eq!(Err(ParseError::new(13, 6, 1, "'Button B: '")), parse_machines(input));
 
-            if let Some(token) = calculate_tokens_with_math(button_a, button_b, prize) {
-                total_tokens += token
-            }
-            index += 3;
-        }
-        total_tokens
-    }
+        let input = indoc! {"
+        Button A: X+94, Y+34
+        Button B: X+22, Y-67
+        "};
+        assert_eq!(Err(ParseError::new(13, 2, 11, "'X+<n>, Y+<n>'")), parse_machines(input));
 
-    fn parse_button(raw_button: &String) -> (isize, isize) {
-        let button_pattern = Regex::new(r"X\+(\d+), Y\+(\d+)").unwrap();
-        parse_tuple(raw_button, button_pattern)
+        assert_eq!(Err(ParseError::new(13, 2, 1, "'Button B: '")), parse_machines("Button A: X+94, Y+34"));
     }
 
-    fn parse_prize(raw_button: &String, extend: bool) -> (isize, isize) {
-        let button_pattern = Regex::new(r"X=(\d+), Y=(\d+)").unwrap();
-        let (x, y) = parse_tuple(raw_button, button_pattern);
-        if !extend {
-            return (x, y);
-        }
-
-        let ext_x = 10000000000000 + x;
-        let ext_y = 10000000000000 + y;
-        (ext_x, ext_y)
-    }
+    #[test]
+    fn it_solves_puzzles() {
+        let input = &personal_input!("input_13");
 
-    fn parse_tuple(line: &String, pattern: Regex) -> (isize, isize) {
-        pattern
-            .captures_iter(line)
-            .map(|c| c.extract())
-            .map(|(_, [first, second])| {
-                (first.parse::<isize>().unwrap(), second.parse::<isize>().unwrap())
-            }).next().unwrap()
+        assert_eq!(Ok(38839), calculate_total_tokens(input, false));
+        assert_eq!(Ok(75200131617108), calculate_total_tokens(input, true)); // too high
     }
-}
\ No newline at end of file
+}
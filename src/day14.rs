@@ -1,10 +1,229 @@
+use crate::geometry::Point;
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, GrayImage, ImageResult, Luma, Rgba, RgbaImage};
+use regex::Regex;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+pub struct Day14;
+
+impl Solver for Day14 {
+    type Input = Vec<Robot>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_robots(input)
+    }
+
+    fn part1(&self, robots: &Self::Input) -> Answer {
+        safety_factor(robots, (101, 103)).into()
+    }
+
+    fn part2(&self, robots: &Self::Input) -> Answer {
+        find_easter_egg(robots, (101, 103)).ok_or("the robots never gather into a picture").into()
+    }
+}
+
+/// The position and the speed of a robot
+pub type Robot = (Point, Point);
+
+/// The picture appears when the robots are the least spread, the x and y positions repeat every
+/// `width` and `height` seconds so the best ones are combined with the chinese remainder theorem
+pub fn find_easter_egg(robots: &[Robot], dimensions: (isize, isize)) -> Option<usize> {
+    let best_x = (0..dimensions.0)
+        .min_by_key(|&t| spread(robots.iter().map(|&(position, speed)| move_robot(position, speed, dimensions, t).x)))?;
+    let best_y = (0..dimensions.1)
+        .min_by_key(|&t| spread(robots.iter().map(|&(position, speed)| move_robot(position, speed, dimensions, t).y)))?;
+
+    chinese_remainder((best_x, dimensions.0), (best_y, dimensions.1)).map(|t| t as usize)
+}
+
+pub fn find_easter_egg_second(input: &str, dimensions: (isize, isize)) -> Result<Option<usize>, ParseError> {
+    Ok(find_easter_egg(&parse_robots(input)?, dimensions))
+}
+
+/// The variance times the square of the number of values, so that it stays an integer
+fn spread(values: impl Iterator<Item = isize>) -> isize {
+    let (count, sum, squares) = values.fold((0, 0, 0), |(count, sum, squares), v| (count + 1, sum + v, squares + v * v));
+    count * squares - sum * sum
+}
+
+/// The smallest t such that t = a mod m and t = b mod n, None when there is none
+fn chinese_remainder((a, m): (isize, isize), (b, n): (isize, isize)) -> Option<isize> {
+    (0..n).map(|k| a + k * m).find(|t| t.rem_euclid(n) == b)
+}
+
+/// Robots are black on a white background, one pixel per tile
+pub fn render_frame(robots: &[Robot], dimensions: (isize, isize), second: usize) -> GrayImage {
+    let mut image = GrayImage::from_pixel(dimensions.0 as u32, dimensions.1 as u32, Luma([255]));
+    for &(position, speed) in robots {
+        let Point { x, y } = move_robot(position, speed, dimensions, second as isize);
+        image.put_pixel(x as u32, y as u32, Luma([0]));
+    }
+    image
+}
+
+pub fn save_frame(robots: &[Robot], dimensions: (isize, isize), second: usize, path: &Path) -> ImageResult<()> {
+    render_frame(robots, dimensions, second).save(path)
+}
+
+/// The robots after `second` seconds, every position is computed from the start with `move_robot`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    robots: Vec<Robot>,
+    dimensions: (isize, isize),
+    second: usize,
+}
+
+impl Simulation {
+    pub fn new(robots: Vec<Robot>, dimensions: (isize, isize)) -> Self {
+        Self { robots, dimensions, second: 0 }
+    }
+
+    pub fn second(&self) -> usize {
+        self.second
+    }
+
+    pub fn advance(&mut self, seconds: usize) {
+        self.second += seconds;
+    }
+
+    pub fn jump_to(&mut self, second: usize) {
+        self.second = second;
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point> + '_ {
+        self.robots.iter()
+            .map(|&(position, speed)| move_robot(position, speed, self.dimensions, self.second as isize))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationOptions {
+    pub seconds: Range<usize>,
+    /// Pixels per tile side
+    pub scale: u32,
+    pub frame_delay_ms: u32,
+    /// Robot i is drawn with colour i modulo the number of colours, a single colour draws them all alike
+    pub colours: Vec<[u8; 3]>,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            seconds: 0..100,
+            scale: 4,
+            frame_delay_ms: 100,
+            colours: vec![[230, 25, 75], [60, 180, 75], [0, 130, 200], [245, 130, 48], [145, 30, 180]],
+        }
+    }
+}
+
+/// Robots are drawn on a white background, the simulation is left at the last second of the range
+pub fn write_gif(simulation: &mut Simulation, options: &AnimationOptions, writer: impl Write) -> ImageResult<()> {
+    let mut encoder = GifEncoder::new(writer);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for second in options.seconds.clone() {
+        simulation.jump_to(second);
+        let delay = Delay::from_numer_denom_ms(options.frame_delay_ms, 1);
+        encoder.encode_frame(Frame::from_parts(render_colour_frame(simulation, options), 0, 0, delay))?;
+    }
+    Ok(())
+}
+
+pub fn save_gif(simulation: &mut Simulation, options: &AnimationOptions, path: &Path) -> ImageResult<()> {
+    write_gif(simulation, options, BufWriter::new(File::create(path)?))
+}
+
+fn render_colour_frame(simulation: &Simulation, options: &AnimationOptions) -> RgbaImage {
+    let (width, height) = (simulation.dimensions.0 as u32, simulation.dimensions.1 as u32);
+    let scale = options.scale.max(1);
+
+    let mut image = RgbaImage::from_pixel(width * scale, height * scale, Rgba([255, 255, 255, 255]));
+    for (index, Point { x, y }) in simulation.positions().enumerate() {
+        let [r, g, b] = options.colours.get(index % options.colours.len().max(1)).copied().unwrap_or([0, 0, 0]);
+        for dy in 0..scale {
+            for dx in 0..scale {
+                image.put_pixel(x as u32 * scale + dx, y as u32 * scale + dy, Rgba([r, g, b, 255]));
+            }
+        }
+    }
+    image
+}
+
+pub fn calculate_safety_factor(input: &str, dimensions: (isize, isize)) -> Result<usize, ParseError> {
+    Ok(safety_factor(&parse_robots(input)?, dimensions))
+}
+
+fn safety_factor(robots: &[Robot], dimensions: (isize, isize)) -> usize {
+    let mut first = 0;
+    let mut second = 0;
+    let mut third = 0;
+    let mut forth = 0;
+
+    for &(position, speed) in robots {
+        let Point { x, y } = move_robot(position, speed, dimensions, 100);
+
+        if x < dimensions.0 / 2 && y < dimensions.1 / 2 {
+            first += 1;
+            continue;
+        }
+
+        if x != dimensions.0 / 2 && y < dimensions.1 / 2 {
+            second += 1;
+            continue;
+        }
+
+        if x < dimensions.0 / 2 && y != dimensions.1 / 2 {
+            third += 1;
+            continue;
+        }
+
+        if x != dimensions.0 / 2 && y != dimensions.1 / 2 {
+            forth += 1;
+        }
+    }
+
+    first * second * third * forth
+}
+
+pub fn parse_robots(input: &str) -> Result<Vec<Robot>, ParseError> {
+    let pattern = Regex::new(r"^p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)$").unwrap();
+
+    lines(14, input)
+        .map(|line| parse_robot(&pattern, &line))
+        .collect()
+}
+
+fn parse_robot(pattern: &Regex, line: &Line) -> Result<Robot, ParseError> {
+    let (_, [x, y, vx, vy]) = pattern
+        .captures(line.text)
+        .ok_or(line.error_at(line.text, "'p=<x>,<y> v=<dx>,<dy>'"))?
+        .extract();
+
+    Ok((
+        Point::new(line.parse::<isize>(x, "a number")?, line.parse::<isize>(y, "a number")?),
+        Point::new(line.parse::<isize>(vx, "a number")?, line.parse::<isize>(vy, "a number")?)
+    ))
+}
+
+pub fn move_robot(robot: Point, speed: Point, dimensions: (isize, isize), times: isize) -> Point {
+    let moved = robot + speed * times;
+    Point::new(moved.x.rem_euclid(dimensions.0), moved.y.rem_euclid(dimensions.1))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
-    use image::{GrayImage, Luma};
+    use super::*;
+    use crate::personal_input;
+    use image::codecs::gif::GifDecoder;
+    use image::AnimationDecoder;
     use indoc::indoc;
-    use regex::Regex;
-    use std::collections::HashSet;
+    use std::io::Cursor;
 
     #[test]
     fn it_calculates_safety_factor() {
@@ -23,104 +242,86 @@ mod tests {
         p=9,5 v=-3,-3
         "};
 
-        assert_eq!(12, calculate_safety_factor(input, (11, 7)));
+        assert_eq!(Ok(12), calculate_safety_factor(input, (11, 7)));
     }
 
     #[test]
-    fn it_solves_both_puzzles() {
-        let input = &read_input_file("input_14");
-
-        assert_eq!(221655456, calculate_safety_factor(input, (101, 103)));
-
-        // 7858. the result is the image having the smallest size
-        show_robots(input, (101, 103));
+    fn it_reports_invalid_robots() {
+        assert_eq!(
+            Err(ParseError::new(14, 2, 1, "'p=<x>,<y> v=<dx>,<dy>'")),
+            parse_robots("p=0,4 v=3,-3\np=6,3 v=-1")
+        );
     }
 
-    fn show_robots(input: &str, dimensions: (isize, isize)) {
-        let mut robots = parse_robots(input);
-
-        for i in 1..=10000 {
-            let mut img = GrayImage::new(dimensions.0 as u32, dimensions.1 as u32);
-            let mut positions = HashSet::new();
-            for i in 0..robots.len() {
-                let robot = robots.get_mut(i).unwrap();
-                let new_position = move_robot(robot.0, robot.1, dimensions, 1);
-                robot.0 = new_position;
-                positions.insert(new_position.clone());
-            }
-
-            for y in 0..dimensions.1 {
-                for x in 0..dimensions.0 {
-                    let pixel_value = if positions.contains(&(x, y)) {
-                        0
-                    } else {
-                        255
-                    };
-
-                    img.put_pixel(x as u32, y as u32, Luma([pixel_value]));
-                }
-            }
+    #[test]
+    fn it_solves_both_puzzles() {
+        let input = &personal_input!("input_14");
 
-            // create manually the folder /tmp/ct
-            img.save(format!("/tmp/ct/output_{}.png", i)).expect("Failed to save image");
-        }
+        assert_eq!(Ok(221655456), calculate_safety_factor(input, (101, 103)));
+        assert_eq!(Ok(Some(7858)), find_easter_egg_second(input, (101, 103)));
     }
 
-    fn calculate_safety_factor(input: &str, dimensions: (isize, isize)) -> usize {
-        let mut first = 0;
-        let mut second = 0;
-        let mut third = 0;
-        let mut forth = 0;
+    #[test]
+    fn it_finds_the_second_where_robots_gather() {
+        let dimensions = (11, 7);
+        let robots: Vec<Robot> = (0..30)
+            .map(|i| {
+                let speed = Point::new(i % 10 + 1, i % 6 + 1);
+                let gathered = Point::new(4 + i % 3, 2 + i / 3 % 3);
+                (move_robot(gathered, speed, dimensions, -40), speed)
+            })
+            .collect();
 
-        for (position, speed) in parse_robots(input) {
-            let (x, y) = move_robot(position, speed, dimensions, 100);
+        assert_eq!(Some(40), find_easter_egg(&robots, dimensions));
 
-            if x < dimensions.0 / 2 && y < dimensions.1 / 2 {
-                first += 1;
-                continue;
-            }
-
-            if x != dimensions.0 / 2 && y < dimensions.1 / 2 {
-                second += 1;
-                continue;
-            }
-
-            if x < dimensions.0 / 2 && y != dimensions.1 / 2 {
-                third += 1;
-                continue;
-            }
+        let frame = render_frame(&robots, dimensions, 40);
+        let dark_pixels: Vec<_> = frame.enumerate_pixels().filter(|(_, _, pixel)| pixel.0 == [0]).map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(9, dark_pixels.len());
+        assert!(dark_pixels.iter().all(|&(x, y)| (4..7).contains(&x) && (2..5).contains(&y)));
+    }
 
-            if x != dimensions.0 / 2 && y != dimensions.1 / 2 {
-                forth += 1;
-            }
-        }
+    #[test]
+    fn it_moves_the_simulation_with_the_robots() {
+        let mut simulation = Simulation::new(vec![(Point::new(2, 4), Point::new(2, -3))], (11, 7));
 
-        let safety_factor = first * second * third * forth;
-        safety_factor
+        simulation.advance(1);
+        assert_eq!(vec![Point::new(4, 1)], simulation.positions().collect::<Vec<_>>());
+        simulation.advance(4);
+        assert_eq!((5, vec![Point::new(1, 3)]), (simulation.second(), simulation.positions().collect::<Vec<_>>()));
+        simulation.jump_to(0);
+        assert_eq!(vec![Point::new(2, 4)], simulation.positions().collect::<Vec<_>>());
     }
 
-    fn parse_robots(input: &str) -> Vec<((isize, isize), (isize, isize))> {
-        let pattern = Regex::new(r"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)").unwrap();
+    #[test]
+    fn it_writes_an_animated_gif() {
+        let mut simulation = Simulation::new(vec![(Point::new(2, 4), Point::new(2, -3)), (Point::ORIGIN, Point::new(1, 1))], (11, 7));
+        let options = AnimationOptions {
+            seconds: 3..8,
+            scale: 3,
+            frame_delay_ms: 50,
+            colours: vec![[255, 0, 0], [0, 0, 255]],
+        };
 
-        let robots: Vec<_> = read_lines(input).iter().map(|r| parse_robot(&pattern, r)).collect();
-        robots
-    }
+        let mut gif = vec![];
+        write_gif(&mut simulation, &options, &mut gif).unwrap();
+        assert_eq!(7, simulation.second());
 
-    fn parse_robot(pattern: &Regex, robot: &String) -> ((isize, isize), (isize, isize)) {
-        pattern
-            .captures_iter(robot)
-            .map(|c| c.extract())
-            .map(|(_, [x, y, vx, vy])| {
-                (
-                    (x.parse::<isize>().unwrap(), y.parse::<isize>().unwrap()),
-                    (vx.parse::<isize>().unwrap(), vy.parse::<isize>().unwrap())
-                )
-            }).next().unwrap()
+        let frames = GifDecoder::new(Cursor::new(gif)).unwrap().into_frames().collect_frames().unwrap();
+        assert_eq!(5, frames.len());
+        assert_eq!((33, 21), frames[0].buffer().dimensions());
+        assert_eq!(Delay::from_numer_denom_ms(50, 1), frames[0].delay());
+
+        // After 3 seconds the first robot is on (8, 2) and the second one on (3, 3)
+        let pixel = |frame: &Frame, x, y| frame.buffer().get_pixel(x, y).0;
+        assert!(matches!(pixel(&frames[0], 8 * 3 + 2, 2 * 3 + 2), [r, g, b, _] if r > 200 && g < 50 && b < 50));
+        assert!(matches!(pixel(&frames[0], 3 * 3, 3 * 3 + 1), [r, g, b, _] if r < 50 && g < 50 && b > 200));
+        assert!(matches!(pixel(&frames[0], 0, 0), [r, g, b, _] if r > 200 && g > 200 && b > 200));
     }
 
-    fn move_robot(robot: (isize, isize), speed: (isize, isize), dimensions: (isize, isize), times: isize) -> (isize, isize) {
-        let new_x = (robot.0 + times * speed.0).rem_euclid(dimensions.0);
-        let new_y = (robot.1 + times * speed.1).rem_euclid(dimensions.1);
-        (new_x, new_y)
+    #[test]
+    fn it_combines_periods_with_the_chinese_remainder_theorem() {
+        assert_eq!(Some(7858), chinese_remainder((7858 % 101, 101), (7858 % 103, 103)));
+        assert_eq!(Some(0), chinese_remainder((0, 101), (0, 103)));
+        assert_eq!(None, chinese_remainder((1, 4), (0, 6)));
     }
-}
\ No newline at end of file
+}
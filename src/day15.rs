@@ -1,404 +1,721 @@
-#[cfg(test)]
-mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
-    use indoc::indoc;
-    use num::range;
-    use std::collections::HashSet;
-
-    #[test]
-    fn it_executes_instructions() {
-        let input = indoc! {"
-        ########
-        #..O.O.#
-        ##@.O..#
-        #...O..#
-        #.#.O..#
-        #...O..#
-        #......#
-        ########
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::parse_error::{lines, ParseError};
+use crate::solver::{Answer, Solver};
+use itertools::Itertools;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::iter::once;
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    type Input = Warehouse;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_warehouse(input)
+    }
 
-        <^^>>>vv<v>>v<<
-        "};
+    fn part1(&self, warehouse: &Self::Input) -> Answer {
+        let mut map = read_map(&warehouse.map);
+        map.execute(warehouse.instructions.clone());
+        map.sum_all_gps_coordinates().into()
+    }
 
-        let (instructions, mut map) = parse_input(input, read_map);
-        map.execute(instructions);
-        map.show();
+    fn part2(&self, warehouse: &Self::Input) -> Answer {
+        let mut map = read_doubled_map(&warehouse.map);
+        map.execute(warehouse.instructions.clone());
+        map.sum_all_gps_coordinates().into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warehouse {
+    pub map: Grid<char>,
+    pub instructions: Vec<Direction>,
+}
+
+pub fn sum_all_gps_coordinates(input: &str) -> Result<usize, ParseError> {
+    let (instructions, mut map) = parse_input(input, read_map)?;
+    map.execute(instructions);
+    Ok(map.sum_all_gps_coordinates())
+}
+
+pub fn parse_input<T>(input: &str, map_parser: fn(&Grid<char>) -> Map<T>) -> Result<(Vec<Direction>, Map<T>), ParseError> {
+    let warehouse = parse_warehouse(input)?;
+    Ok((warehouse.instructions, map_parser(&warehouse.map)))
+}
+
+pub fn parse_warehouse(input: &str) -> Result<Warehouse, ParseError> {
+    let mut lines = lines(15, input);
+
+    let mut map_lines = vec![];
+    let mut last_line = 0;
+    for line in lines.by_ref() {
+        last_line = line.number;
+        if line.text.is_empty() {
+            break;
+        }
+        map_lines.push(line.text);
     }
 
-    #[test]
-    fn it_sums_all_gps_coordinates() {
-        let input = &read_input_file("test_input_15");
+    let map = Grid::parse(15, &map_lines.join("\n"), "'#', '.', 'O' or '@'", |c| {
+        matches!(c, '#' | '.' | 'O' | '@').then_some(c)
+    })?;
 
-        assert_eq!(10092, sum_all_gps_coordinates(input));
+    if map.height() == 0 {
+        return Err(ParseError::new(15, 1, 1, "a map"));
+    }
+    if map.find_all(&'@').count() != 1 {
+        return Err(ParseError::new(15, 1, 1, "exactly one robot '@' in the map"));
     }
 
-    #[test]
-    fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_15");
+    let mut instructions = vec![];
+    for line in lines {
+        last_line = line.number;
+        for (x, char) in line.text.chars().enumerate() {
+            let direction = Direction::from_char(char)
+                .ok_or(ParseError::new(15, line.number, x + 1, "a move '<', '>', '^' or 'v'"))?;
+            instructions.push(direction);
+        }
+    }
 
-        assert_eq!(1475249, sum_all_gps_coordinates(input));
+    if instructions.is_empty() {
+        return Err(ParseError::new(15, last_line + 1, 1, "an empty line followed by the moves"));
     }
 
-    #[test]
-    fn it_sums_all_gps_coordinates_after_expansion_and_execution() {
-        let input = &read_input_file("test_input_15");
+    Ok(Warehouse { map, instructions })
+}
 
-        let (instructions, mut map) = parse_input(input, read_doubled_map);
-        map.show();
-        map.execute(instructions);
-        map.show();
+pub fn read_map(raw_map: &Grid<char>) -> Map<SimpleBox> {
+    expand_map(raw_map, 1, 1, SimpleBox)
+}
 
-        assert_eq!(9021, map.sum_all_gps_coordinates())
+pub fn read_doubled_map(raw_map: &Grid<char>) -> Map<DoubleBox> {
+    expand_map(raw_map, 2, 1, DoubleBox::at)
+}
+
+/// Every tile becomes `width` x `height` cells, e.g. 4 x 1 for a quadruple-expanded warehouse
+pub fn read_expanded_map(raw_map: &Grid<char>, width: usize, height: usize) -> Map<LargeBox> {
+    expand_map(raw_map, width, height, |origin| LargeBox { origin, width, height })
+}
+
+fn expand_map<T: WarehouseBox>(raw_map: &Grid<char>, width: usize, height: usize, new_box: impl Fn(Point) -> T) -> Map<T> {
+    let expanded = |p: Point| Point::new(p.x * width as isize, p.y * height as isize);
+
+    let walls = raw_map.find_all(&'#')
+        .map(expanded)
+        .flat_map(|corner| tile_cells(corner, width, height))
+        .collect();
+    let robot = raw_map.find(&'@').map(expanded).unwrap_or(Point::ORIGIN);
+    let boxes = raw_map.find_all(&'O').map(expanded).map(new_box);
+
+    Map::new(walls, robot, boxes, (raw_map.width() * width, raw_map.height() * height))
+}
+
+fn tile_cells(corner: Point, width: usize, height: usize) -> Vec<Point> {
+    (0..height as isize)
+        .flat_map(|y| (0..width as isize).map(move |x| corner + Point::new(x, y)))
+        .collect()
+}
+
+/// A box of any shape: the robot pushes it when it walks into one of its cells, and it pushes in
+/// turn every box standing where its cells move to
+pub trait WarehouseBox: Clone + Eq + Hash {
+    fn cells(&self) -> Vec<Point>;
+
+    fn move_to(&self, direction: Direction) -> Self;
+
+    fn gps_coordinate(&self) -> usize;
+
+    /// How a cell of the box is drawn
+    fn symbol(&self, cell: Point) -> char;
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize)]
+pub struct SimpleBox(Point);
+
+impl WarehouseBox for SimpleBox {
+    fn cells(&self) -> Vec<Point> {
+        vec![self.0]
     }
 
-    #[test]
-    fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_15");
-        let (instructions, mut map) = parse_input(input, read_doubled_map);
-        map.execute(instructions);
+    fn move_to(&self, direction: Direction) -> Self {
+        SimpleBox(self.0.step(direction))
+    }
 
-        assert_eq!(1509724, map.sum_all_gps_coordinates());
+    fn gps_coordinate(&self) -> usize {
+        let Point { x, y } = self.0;
+        (100 * y + x) as usize
     }
 
-    fn sum_all_gps_coordinates(input: &String) -> usize {
-        let (instructions, mut map) = parse_input(input, read_map);
-        map.execute(instructions);
-        map.sum_all_gps_coordinates()
+    fn symbol(&self, _: Point) -> char {
+        'O'
     }
+}
 
-    fn parse_input<T>(input: &str, map_parser: fn(Vec<String>) -> Map<T>) -> (Vec<char>, Map<T>) {
-        let lines = read_lines(input);
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize)]
+pub struct DoubleBox {
+    start: Point,
+    end: Point,
+}
 
-        let mut raw_map = vec![];
-        let mut index = 0;
-        while !lines[index].is_empty() {
-            raw_map.push(lines[index].clone());
-            index += 1;
-        }
+impl DoubleBox {
+    fn at(start: Point) -> Self {
+        DoubleBox { start, end: start.step(Direction::Right) }
+    }
+}
 
-        let mut instructions: Vec<char> = vec![];
-        for i in index..lines.len() {
-            let mut instructions_in_line: Vec<char> = lines[i].chars().collect();
-            instructions.append(&mut instructions_in_line);
-        }
+impl WarehouseBox for DoubleBox {
+    fn cells(&self) -> Vec<Point> {
+        vec![self.start, self.end]
+    }
 
+    fn move_to(&self, direction: Direction) -> Self {
+        DoubleBox { start: self.start.step(direction), end: self.end.step(direction) }
+    }
 
-        (instructions, map_parser(raw_map))
+    fn gps_coordinate(&self) -> usize {
+        (self.start.y * 100 + self.start.x) as usize
     }
 
-    fn read_map(raw_map: Vec<String>) -> Map<SimpleBox> {
-        let mut walls = HashSet::new();
-        let mut robot = (0, 0);
-        let mut boxes = HashSet::new();
-        for (y, line) in raw_map.iter().enumerate() {
-            for (x, char) in line.chars().enumerate() {
-                if char == '#' {
-                    walls.insert((x, y));
-                }
+    fn symbol(&self, cell: Point) -> char {
+        if cell == self.start { '[' } else { ']' }
+    }
+}
+
+/// A box covering `width` x `height` cells from its top left corner
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize)]
+pub struct LargeBox {
+    origin: Point,
+    width: usize,
+    height: usize,
+}
+
+impl WarehouseBox for LargeBox {
+    fn cells(&self) -> Vec<Point> {
+        tile_cells(self.origin, self.width, self.height)
+    }
 
-                if char == '@' {
-                    robot = (x, y);
-                }
+    fn move_to(&self, direction: Direction) -> Self {
+        LargeBox { origin: self.origin.step(direction), ..self.clone() }
+    }
 
-                if char == 'O' {
-                    boxes.insert(SimpleBox((x, y)));
-                }
-            }
-        }
+    fn gps_coordinate(&self) -> usize {
+        (self.origin.y * 100 + self.origin.x) as usize
+    }
 
-        let dimensions = (raw_map.len(), raw_map[0].len());
-        Map { walls, robot, boxes, dimensions }
-    }
-
-    fn read_doubled_map(raw_map: Vec<String>) -> Map<DoubleBox> {
-        let mut walls = HashSet::new();
-        let mut robot = (0, 0);
-        let mut boxes = HashSet::new();
-        for (y, line) in raw_map.iter().enumerate() {
-            let mut x = 0;
-            for char in line.chars() {
-                if char == '#' {
-                    walls.insert((x, y));
-                    walls.insert((x + 1, y));
-                }
-
-                if char == '@' {
-                    robot = (x, y);
-                }
-
-                if char == 'O' {
-                    boxes.insert(DoubleBox { start: (x, y), end: (x + 1, y) });
-                }
-                x += 2;
-            }
+    fn symbol(&self, cell: Point) -> char {
+        match cell.x - self.origin.x {
+            _ if self.width == 1 => 'O',
+            0 => '[',
+            x if x == self.width as isize - 1 => ']',
+            _ => '=',
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Map<T> {
+    walls: HashSet<Point>,
+    robot: Point,
+    boxes: HashSet<T>,
+    /// The box covering each cell
+    cells: HashMap<Point, T>,
+    dimensions: (usize, usize),
+}
+
+impl<T: WarehouseBox> Map<T> {
+    fn new(walls: HashSet<Point>, robot: Point, boxes: impl IntoIterator<Item = T>, dimensions: (usize, usize)) -> Self {
+        let mut map = Map { walls, robot, boxes: HashSet::new(), cells: HashMap::new(), dimensions };
+        for b in boxes {
+            map.insert_box(b);
         }
+        map
+    }
 
-        let dimensions = (raw_map[0].len() * 2, raw_map.len());
-        Map { walls, robot, boxes, dimensions }
+    pub fn execute(&mut self, instructions: Vec<Direction>) {
+        for instruction in instructions {
+            self.execute_instruction(instruction);
+        }
     }
 
-    #[derive(Debug, Clone, Hash, Eq, PartialEq)]
-    struct DoubleBox {
-        start: Position,
-        end: Position,
+    pub fn record(mut self, instructions: &[Direction]) -> Timeline<T> {
+        let moves = instructions.iter().map(|&instruction| self.execute_instruction(instruction)).collect();
+        Timeline { map: self, moves, position: instructions.len() }
     }
 
-    impl DoubleBox {
-        fn move_to(&self, instruction: char) -> Self {
-            let (sx, sy) = self.start;
-            let (ex, ey) = self.end;
-            match instruction {
-                '^' => DoubleBox { start: (sx, sy - 1), end: (ex, ey - 1) },
-                'v' => DoubleBox { start: (sx, sy + 1), end: (ex, ey + 1) },
-                '<' => DoubleBox { start: (sx - 1, sy), end: (sx, sy) },
-                '>' => DoubleBox { start: (ex, sy), end: (ex + 1, sy) },
-                _ => panic!("invalid instruction")
-            }
-        }
+    /// Walks the cells the robot and the pushed boxes move into, the whole move is blocked as soon
+    /// as one of them is a wall
+    fn execute_instruction(&mut self, instruction: Direction) -> Move<T> {
+        let mut pushed = vec![];
+        let mut seen = HashSet::new();
+        let mut to_visit = vec![self.robot.step(instruction)];
 
-        fn neighbours(&self, instruction: char) -> HashSet<Self> {
-            let (sx, sy) = self.start;
-            let (ex, ey) = self.end;
-            match instruction {
-                '^' => HashSet::from([
-                    DoubleBox { start: (sx - 1, sy - 1), end: (sx, ey - 1) },
-                    DoubleBox { start: (sx, sy - 1), end: (ex, ey - 1) },
-                    DoubleBox { start: (ex, sy - 1), end: (ex + 1, ey - 1) },
-                ]),
-                'v' => HashSet::from([
-                    DoubleBox { start: (sx - 1, sy + 1), end: (sx, ey + 1) },
-                    DoubleBox { start: (sx, sy + 1), end: (ex, ey + 1) },
-                    DoubleBox { start: (ex, sy + 1), end: (ex + 1, ey + 1) },
-                ]),
-                '<' => HashSet::from([
-                    DoubleBox { start: (sx - 2, sy), end: (sx - 1, sy) },
-                ]),
-                '>' => HashSet::from([
-                    DoubleBox { start: (ex + 1, sy), end: (ex + 2, sy) },
-                ]),
-                _ => panic!("invalid instruction")
+        while let Some(cell) = to_visit.pop() {
+            if self.walls.contains(&cell) {
+                return Move::blocked(instruction);
+            }
+            let Some(b) = self.cells.get(&cell) else {
+                continue;
+            };
+            if !seen.insert(b.clone()) {
+                continue;
             }
+
+            let moved = b.move_to(instruction);
+            let cells = b.cells();
+            to_visit.extend(moved.cells().into_iter().filter(|cell| !cells.contains(cell)));
+            pushed.push((b.clone(), moved));
         }
+
+        let event = Move { direction: instruction, robot_delta: instruction.offset(), pushed };
+        self.apply(&event);
+        event
     }
 
-    impl Map<DoubleBox> {
-        fn execute(&mut self, instructions: Vec<char>) {
-            for instruction in instructions {
-                self.execute_instruction(instruction)
-            }
+    pub fn apply(&mut self, event: &Move<T>) {
+        self.robot += event.robot_delta;
+        for (before, _) in &event.pushed {
+            self.remove_box(before);
         }
-
-        fn sum_all_gps_coordinates(&self) -> usize {
-            let mut result = 0;
-            for b in &self.boxes {
-                result += b.start.1 * 100 + b.start.0;
-            }
-            result
+        for (_, after) in &event.pushed {
+            self.insert_box(after.clone());
         }
+    }
 
-        fn boxes_for_position_and_direction((x, y): Position, instruction: char) -> HashSet<DoubleBox> {
-            match instruction {
-                '^' => HashSet::from([
-                    DoubleBox { start: (x - 1, y), end: (x, y) },
-                    DoubleBox { start: (x, y), end: (x + 1, y) }
-                ]),
-                'v' => HashSet::from([
-                    DoubleBox { start: (x - 1, y), end: (x, y) },
-                    DoubleBox { start: (x, y), end: (x + 1, y) }
-                ]),
-                '<' => HashSet::from([
-                    DoubleBox { start: (x - 1, y), end: (x, y) }
-                ]),
-                '>' => HashSet::from([
-                    DoubleBox { start: (x, y), end: (x + 1, y) }
-                ]),
-                _ => panic!("invalid instruction")
-            }
+    pub fn revert(&mut self, event: &Move<T>) {
+        self.robot -= event.robot_delta;
+        for (_, after) in &event.pushed {
+            self.remove_box(after);
         }
+        for (before, _) in &event.pushed {
+            self.insert_box(before.clone());
+        }
+    }
 
-        fn execute_instruction(&mut self, instruction: char) {
-            let next_robot_position = robot_step(&self.robot, instruction);
-            if self.walls.contains(&next_robot_position) {
-                return;
-            }
+    fn insert_box(&mut self, b: T) {
+        for cell in b.cells() {
+            self.cells.insert(cell, b.clone());
+        }
+        self.boxes.insert(b);
+    }
 
-            let mut current_boxes = Self::boxes_for_position_and_direction(next_robot_position, instruction);
-            if self.boxes.intersection(&current_boxes).next().is_none() {
-                self.robot = next_robot_position;
-                return;
-            }
+    fn remove_box(&mut self, b: &T) {
+        for cell in b.cells() {
+            self.cells.remove(&cell);
+        }
+        self.boxes.remove(b);
+    }
 
-            let mut boxes_to_add = HashSet::new();
-            let mut boxes_to_remove = HashSet::new();
-
-            while self.boxes.intersection(&current_boxes).next().is_some() {
-                boxes_to_remove.extend(current_boxes.clone());
-
-                let mut next_boxes = HashSet::new();
-                let mut next_positions = HashSet::new();
-
-                for b in self.boxes.intersection(&current_boxes) {
-                    let bxs = b.move_to(instruction);
-                    next_positions.insert(bxs.start);
-                    next_positions.insert(bxs.end);
-                    boxes_to_add.insert(bxs);
-                    let neighbours = b.neighbours(instruction);
-                    for n in neighbours {
-                        if !self.boxes.contains(&n) {
-                            continue;
-                        }
-                        next_boxes.insert(n);
-                    }
-                }
-
-                if self.walls.intersection(&next_positions).next().is_some() {
-                    boxes_to_remove.clear();
-                    break;
-                }
-
-                current_boxes = next_boxes;
-            }
+    pub fn sum_all_gps_coordinates(&self) -> usize {
+        self.boxes.iter()
+            .map(|b| b.gps_coordinate())
+            .sum()
+    }
 
-            if !boxes_to_remove.is_empty() {
-                for b in boxes_to_remove {
-                    self.boxes.remove(&b);
-                }
-                self.boxes.extend(boxes_to_add);
-                self.robot = next_robot_position;
-            }
+    pub fn render(&self) -> String {
+        let mut grid = Grid::new(self.dimensions.0, self.dimensions.1, '.');
+        for &wall in &self.walls {
+            grid.set(wall, '#');
         }
-        fn show(&self) {
-            for y in range(0, self.dimensions.1) {
-                let mut x = 0;
-                while x < self.dimensions.0 {
-                    if self.walls.contains(&(x, y)) {
-                        print!("#");
-                        x += 1;
-                        continue;
-                    }
-
-                    if self.robot == (x, y) {
-                        print!("@");
-                        x += 1;
-                        continue;
-                    }
-
-                    if self.boxes.contains(&DoubleBox { start: (x, y), end: (x + 1, y) }) {
-                        print!("[]");
-                        x += 2;
-                        continue;
-                    }
-
-                    print!(".");
-                    x += 1;
-                }
-                println!();
-            }
+        for (&cell, b) in &self.cells {
+            grid.set(cell, b.symbol(cell));
         }
+        grid.set(self.robot, '@');
+        grid.to_string()
     }
 
-    #[derive(Debug)]
-    struct Map<T> {
-        walls: HashSet<Position>,
-        robot: Position,
-        boxes: HashSet<T>,
-        dimensions: (usize, usize),
+    pub fn show(&self) {
+        println!("{}", self.render());
     }
-
-    fn robot_step((x, y): &Position, instruction: char) -> Position {
-        match instruction {
-            '<' => (x - 1, *y),
-            '>' => (x + 1, *y),
-            '^' => (*x, y - 1),
-            'v' => (*x, y + 1),
-            _ => panic!("unknown instruction")
-        }
+}
+
+/// A reversible instruction: the robot moves by `robot_delta` and every pushed box goes from its
+/// first position to its second one. A blocked instruction moves nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Move<T> {
+    pub direction: Direction,
+    pub robot_delta: Point,
+    pub pushed: Vec<(T, T)>,
+}
+
+impl<T> Move<T> {
+    fn blocked(direction: Direction) -> Self {
+        Move { direction, robot_delta: Point::ORIGIN, pushed: vec![] }
+    }
+}
+
+/// Every move of a run, the map is the state after the first `position` instructions
+#[derive(Debug, Clone)]
+pub struct Timeline<T> {
+    map: Map<T>,
+    moves: Vec<Move<T>>,
+    position: usize,
+}
+
+impl<T: WarehouseBox> Timeline<T> {
+    pub fn map(&self) -> &Map<T> {
+        &self.map
     }
 
-    impl Map<SimpleBox> {
-        fn execute(&mut self, instructions: Vec<char>) {
-            for instruction in instructions {
-                self.execute_instruction(instruction)
-            }
-        }
+    pub fn moves(&self) -> &[Move<T>] {
+        &self.moves
+    }
 
-        fn execute_instruction(&mut self, instruction: char) {
-            let next_robot_position = robot_step(&self.robot, instruction);
-            if self.walls.contains(&next_robot_position) {
-                return;
-            }
+    pub fn position(&self) -> usize {
+        self.position
+    }
 
-            if !self.boxes.contains(&SimpleBox(next_robot_position.clone())) {
-                self.robot = next_robot_position;
-                return;
-            }
+    pub fn step_forward(&mut self) -> Option<&Move<T>> {
+        let event = self.moves.get(self.position)?;
+        self.map.apply(event);
+        self.position += 1;
+        Some(event)
+    }
 
-            let mut current_box = SimpleBox(next_robot_position.clone());
-            let mut box_to_remove = Some(current_box.clone());
-            while self.boxes.contains(&current_box) {
-                current_box = current_box.move_to(instruction);
-                if self.walls.contains(&current_box.0) {
-                    box_to_remove = None;
-                }
-            }
+    pub fn step_backward(&mut self) -> Option<&Move<T>> {
+        self.position = self.position.checked_sub(1)?;
+        let event = &self.moves[self.position];
+        self.map.revert(event);
+        Some(event)
+    }
 
-            if let Some(b) = box_to_remove {
-                self.boxes.remove(&b);
-                self.boxes.insert(current_box);
-                self.robot = next_robot_position;
-            }
+    /// Positions past the last instruction stop at the end of the run
+    pub fn jump_to(&mut self, position: usize) {
+        while self.position < position.min(self.moves.len()) {
+            self.step_forward();
         }
-        fn show(&self) {
-            for y in range(0, self.dimensions.1) {
-                for x in range(0, self.dimensions.0) {
-                    if self.walls.contains(&(x, y)) {
-                        print!("#");
-                        continue;
-                    }
-
-                    if self.robot == (x, y) {
-                        print!("@");
-                        continue;
-                    }
-
-                    if self.boxes.contains(&SimpleBox((x, y))) {
-                        print!("O");
-                        continue;
-                    }
-
-                    print!(".");
-                }
-                println!();
-            }
+        while self.position > position {
+            self.step_backward();
         }
+    }
 
-        fn sum_all_gps_coordinates(&self) -> usize {
-            self.boxes.iter()
-                .map(|b| b.box_gps_coordinates())
-                .sum()
+    /// The warehouse before the first instruction as instruction 0, then one JSON object per
+    /// instruction, so that any state of the run can be rebuilt from the lines alone
+    pub fn to_json_lines(&self) -> String
+    where
+        T: Serialize,
+    {
+        let mut initial = self.map.clone();
+        for event in self.moves[..self.position].iter().rev() {
+            initial.revert(event);
         }
+
+        let (width, height) = initial.dimensions;
+        let warehouse = json!({
+            "width": width,
+            "height": height,
+            "walls": initial.walls.iter().sorted().collect_vec(),
+            "robot": initial.robot,
+            "boxes": initial.boxes.iter().sorted_by_key(|b| b.cells()).collect_vec(),
+        });
+
+        once(json!({ "instruction": 0, "warehouse": warehouse }).to_string())
+            .chain(self.moves.iter()
+                .enumerate()
+                .map(|(index, event)| json!({ "instruction": index + 1, "move": event }).to_string()))
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personal_input;
+    use indoc::indoc;
+
+    const LARGER_EXAMPLE: &str = indoc! {"
+        ##########
+        #..O..O.O#
+        #......O.#
+        #.OO..O.O#
+        #..O@..O.#
+        #O#..O...#
+        #O..O..O.#
+        #.OO.O.OO#
+        #....O...#
+        ##########
+
+        <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+        vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+        ><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+        <<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+        ^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+        ^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+        >^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+        <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+        ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+        v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
+        "};
+
+    #[test]
+    fn it_executes_instructions() {
+        let input = indoc! {"
+        ########
+        #..O.O.#
+        ##@.O..#
+        #...O..#
+        #.#.O..#
+        #...O..#
+        #......#
+        ########
+
+        <^^>>>vv<v>>v<<
+        "};
+
+        let (instructions, mut map) = parse_input(input, read_map).unwrap();
+        map.execute(instructions);
+        map.show();
     }
 
-    #[derive(Debug, Clone, Hash, Eq, PartialEq)]
-    struct SimpleBox(Position);
+    fn large_box(x: isize, y: isize, width: usize, height: usize) -> LargeBox {
+        LargeBox { origin: Point::new(x, y), width, height }
+    }
+
+    fn bordered_map(width: usize, height: usize, robot: Point, boxes: Vec<LargeBox>) -> Map<LargeBox> {
+        let walls = Grid::new(width, height, ()).positions()
+            .filter(|p| p.x == 0 || p.y == 0 || p.x == width as isize - 1 || p.y == height as isize - 1)
+            .collect();
+        Map::new(walls, robot, boxes, (width, height))
+    }
+
+    #[test]
+    fn it_pushes_boxes_of_any_size() {
+        let Warehouse { map: raw_map, instructions } = parse_warehouse(LARGER_EXAMPLE).unwrap();
+
+        let mut map = read_expanded_map(&raw_map, 1, 1);
+        map.execute(instructions.clone());
+        assert_eq!(10092, map.sum_all_gps_coordinates());
+
+        let mut map = read_expanded_map(&raw_map, 2, 1);
+        assert_eq!(read_doubled_map(&raw_map).render(), map.render());
+        map.execute(instructions);
+        assert_eq!(9021, map.sum_all_gps_coordinates());
+    }
+
+    #[test]
+    fn it_pushes_quadruple_wide_boxes() {
+        let boxes = vec![large_box(3, 3, 4, 1), large_box(1, 2, 4, 1), large_box(6, 2, 4, 1)];
+        let mut map = bordered_map(12, 6, Point::new(5, 4), boxes);
+
+        assert_eq!(3, map.execute_instruction(Direction::Up).pushed.len());
+        assert_eq!(
+            indoc! {"
+            ############
+            #[==].[==].#
+            #..[==]....#
+            #....@.....#
+            #..........#
+            ############"},
+            map.render()
+        );
+
+        assert_eq!(Move::blocked(Direction::Up), map.execute_instruction(Direction::Up));
+        assert_eq!(Point::new(5, 3), map.robot);
+    }
+
+    #[test]
+    fn it_pushes_tall_boxes_sideways() {
+        let boxes = vec![large_box(3, 1, 1, 2), large_box(4, 2, 1, 2)];
+        let mut map = bordered_map(8, 5, Point::new(2, 2), boxes);
+
+        map.execute(vec![Direction::Right, Direction::Right]);
+        assert_eq!(
+            indoc! {"
+            ########
+            #....O.#
+            #...@OO#
+            #.....O#
+            ########"},
+            map.render()
+        );
+        assert_eq!(100 + 5 + 200 + 6, map.sum_all_gps_coordinates());
+
+        assert_eq!(Move::blocked(Direction::Right), map.execute_instruction(Direction::Right));
+    }
+
+    #[test]
+    fn it_steps_through_the_timeline() {
+        let input = indoc! {"
+        ########
+        #..O.O.#
+        ##@.O..#
+        #...O..#
+        #.#.O..#
+        #...O..#
+        #......#
+        ########
 
-    impl SimpleBox {
-        fn move_to(&self, instruction: char) -> Self {
-            let (x, y) = self.0;
-            match instruction {
-                '<' => SimpleBox((x - 1, y)),
-                '>' => SimpleBox((x + 1, y)),
-                '^' => SimpleBox((x, y - 1)),
-                'v' => SimpleBox((x, y + 1)),
-                _ => panic!("unknown instruction")
+        <^^>>>vv<v>>v<<
+        "};
+
+        let (instructions, map) = parse_input(input, read_map).unwrap();
+        let initial = map.render();
+        let mut timeline = map.record(&instructions);
+        let last = timeline.map().render();
+
+        assert_eq!((15, 2028), (timeline.position(), timeline.map().sum_all_gps_coordinates()));
+        assert_eq!(None, timeline.step_forward());
+
+        timeline.jump_to(0);
+        assert_eq!(initial, timeline.map().render());
+        assert_eq!(None, timeline.step_backward());
+
+        // The first move hits a wall, the fourth one pushes a box
+        assert_eq!(Some(&Move { direction: Direction::Left, robot_delta: Point::ORIGIN, pushed: vec![] }), timeline.step_forward());
+        timeline.jump_to(3);
+        assert_eq!(
+            Some(&Move {
+                direction: Direction::Right,
+                robot_delta: Point::new(1, 0),
+                pushed: vec![(SimpleBox(Point::new(3, 1)), SimpleBox(Point::new(4, 1)))],
+            }),
+            timeline.step_forward()
+        );
+        assert_eq!("########\n#..@OO.#", &timeline.map().render()[..17]);
+
+        timeline.step_backward();
+        assert_eq!("########\n#.@O.O.#", &timeline.map().render()[..17]);
+
+        timeline.jump_to(100);
+        assert_eq!((15, last), (timeline.position(), timeline.map().render()));
+    }
+
+    #[test]
+    fn it_undoes_pushes_in_the_wide_warehouse() {
+        let (instructions, map) = parse_input(LARGER_EXAMPLE, read_doubled_map).unwrap();
+        let initial = map.render();
+        let mut timeline = map.record(&instructions);
+
+        assert_eq!(9021, timeline.map().sum_all_gps_coordinates());
+        assert!(timeline.moves().iter().any(|event| event.pushed.len() > 2));
+
+        timeline.jump_to(350);
+        let middle = timeline.map().render();
+        timeline.jump_to(0);
+        assert_eq!(initial, timeline.map().render());
+
+        timeline.jump_to(350);
+        assert_eq!(middle, timeline.map().render());
+        timeline.jump_to(instructions.len());
+        assert_eq!(9021, timeline.map().sum_all_gps_coordinates());
+    }
+
+    #[test]
+    fn it_exports_the_timeline_as_json_lines() {
+        let (instructions, map) = parse_input("#####\n#@O.#\n#####\n\n><>", read_map).unwrap();
+        let json_lines = map.record(&instructions).to_json_lines();
+
+        assert_eq!(
+            vec![
+                r#"{"instruction":0,"warehouse":{"boxes":[{"x":2,"y":1}],"height":3,"robot":{"x":1,"y":1},"walls":[{"x":0,"y":0},{"x":0,"y":1},{"x":0,"y":2},{"x":1,"y":0},{"x":1,"y":2},{"x":2,"y":0},{"x":2,"y":2},{"x":3,"y":0},{"x":3,"y":2},{"x":4,"y":0},{"x":4,"y":1},{"x":4,"y":2}],"width":5}}"#,
+                r#"{"instruction":1,"move":{"direction":"Right","pushed":[[{"x":2,"y":1},{"x":3,"y":1}]],"robot_delta":{"x":1,"y":0}}}"#,
+                r#"{"instruction":2,"move":{"direction":"Left","pushed":[],"robot_delta":{"x":-1,"y":0}}}"#,
+                r#"{"instruction":3,"move":{"direction":"Right","pushed":[],"robot_delta":{"x":1,"y":0}}}"#,
+            ],
+            json_lines.lines().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_rebuilds_the_final_map_from_the_json_lines() {
+        let (instructions, map) = parse_input(LARGER_EXAMPLE, read_map).unwrap();
+        let mut timeline = map.record(&instructions);
+        timeline.jump_to(400);
+        let json_lines = timeline.to_json_lines();
+        timeline.jump_to(instructions.len());
+
+        let records: Vec<serde_json::Value> = json_lines.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let point = |value: &serde_json::Value| serde_json::from_value::<Point>(value.clone()).unwrap();
+        let points = |value: &serde_json::Value| value.as_array().unwrap().iter().map(point).collect::<Vec<_>>();
+
+        let warehouse = &records[0]["warehouse"];
+        let size = |key| warehouse[key].as_u64().unwrap() as usize;
+        let mut robot = point(&warehouse["robot"]);
+        let mut boxes: HashSet<Point> = points(&warehouse["boxes"]).into_iter().collect();
+        for event in records[1..].iter().map(|record| &record["move"]) {
+            robot += point(&event["robot_delta"]);
+            let pushed: Vec<_> = event["pushed"].as_array().unwrap().iter().map(points).collect();
+            for cells in &pushed {
+                boxes.remove(&cells[0]);
             }
+            boxes.extend(pushed.iter().map(|cells| cells[1]));
         }
 
-        fn box_gps_coordinates(&self) -> usize {
-            let (x, y) = self.0;
-            100 * y + x
+        let mut grid = Grid::new(size("width"), size("height"), '.');
+        for wall in points(&warehouse["walls"]) {
+            grid.set(wall, '#');
+        }
+        for &b in &boxes {
+            grid.set(b, 'O');
         }
+        grid.set(robot, '@');
+        assert_eq!(timeline.map().render(), grid.to_string());
+    }
+
+    #[test]
+    fn it_reports_invalid_warehouses() {
+        let input = indoc! {"
+        #####
+        #@O.#
+        #####
+
+        <<x>
+        "};
+        assert_eq!(Some(ParseError::new(15, 5, 3, "a move '<', '>', '^' or 'v'")), parse_warehouse(input).err());
+
+        let input = indoc! {"
+        #####
+        #@O.#
+        #####
+        "};
+        assert_eq!(Some(ParseError::new(15, 4, 1, "an empty line followed by the moves")), parse_warehouse(input).err());
+
+        let input = indoc! {"
+        #####
+        #.O.#
+        #####
+
+        <<>
+        "};
+        assert_eq!(Some(ParseError::new(15, 1, 1, "exactly one robot '@' in the map")), parse_warehouse(input).err());
     }
 
-    type Position = (usize, usize);
-}
\ No newline at end of file
+    #[test]
+    fn it_sums_all_gps_coordinates() {
+        let input = LARGER_EXAMPLE;
+
+        assert_eq!(Ok(10092), sum_all_gps_coordinates(input));
+    }
+
+    #[test]
+    fn it_solves_first_puzzle() {
+        let input = &personal_input!("input_15");
+
+        assert_eq!(Ok(1475249), sum_all_gps_coordinates(input));
+    }
+
+    #[test]
+    fn it_sums_all_gps_coordinates_after_expansion_and_execution() {
+        let input = LARGER_EXAMPLE;
+
+        let (instructions, mut map) = parse_input(input, read_doubled_map).unwrap();
+        map.show();
+        map.execute(instructions);
+        map.show();
+
+        assert_eq!(9021, map.sum_all_gps_coordinates())
+    }
+
+    #[test]
+    fn it_solves_second_puzzle() {
+        let input = &personal_input!("input_15");
+        let (instructions, mut map) = parse_input(input, read_doubled_map).unwrap();
+        map.execute(instructions);
+
+        assert_eq!(1509724, map.sum_all_gps_coordinates());
+    }
+}
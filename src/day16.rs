@@ -1,9 +1,274 @@
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::parse_error::ParseError;
+use crate::search::dijkstra;
+use crate::solver::{Answer, Solver};
+use image::{ImageResult, Rgb, RgbImage};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+pub struct Day16;
+
+const UNREACHABLE_END: &str = "the end tile cannot be reached from the start tile";
+
+impl Solver for Day16 {
+    type Input = (HashSet<Point>, Point, Point);
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_map(input)
+    }
+
+    fn part1(&self, (walls, start, end): &Self::Input) -> Answer {
+        find_lowest_score(walls, *start, *end).ok_or(UNREACHABLE_END).into()
+    }
+
+    fn part2(&self, (walls, start, end): &Self::Input) -> Answer {
+        best_paths(walls, *start, *end, &ReindeerRules::default())
+            .map(|paths| paths.tiles().len())
+            .ok_or(UNREACHABLE_END)
+            .into()
+    }
+}
+
+pub fn parse_map(input: &str) -> Result<(HashSet<Point>, Point, Point), ParseError> {
+    let maze = Grid::parse(16, input, "'#', '.', 'S' or 'E'", |c| matches!(c, '#' | '.' | 'S' | 'E').then_some(c))?;
+
+    let start = maze.find(&'S').ok_or(ParseError::new(16, 1, 1, "a start tile 'S'"))?;
+    let end = maze.find(&'E').ok_or(ParseError::new(16, 1, 1, "an end tile 'E'"))?;
+    Ok((maze.find_all(&'#').collect(), start, end))
+}
+
+pub fn find_lowest_score(walls: &HashSet<Point>, start: Point, end: Point) -> Option<usize> {
+    lowest_score(walls, start, end, &ReindeerRules::default())
+}
+
+pub fn lowest_score(walls: &HashSet<Point>, start: Point, end: Point, rules: &ReindeerRules) -> Option<usize> {
+    dijkstra([(start, Direction::Right)], |node| neighbours(walls, rules, node), |&(position, _)| position == end)
+        .goal_distance()
+}
+
+pub fn tiles_on_best_paths(walls: &HashSet<Point>, start: Point, end: Point) -> HashSet<Point> {
+    best_paths(walls, start, end, &ReindeerRules::default())
+        .map(|paths| paths.tiles())
+        .unwrap_or_default()
+}
+
+type State = (Point, Direction);
+
+/// Every move steps one tile forward, after turning 90° for `turn_cost` or, when allowed, turning
+/// around for twice that cost. Steps are never free, otherwise the best paths could loop forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReindeerRules {
+    step_cost: usize,
+    turn_cost: usize,
+    allow_u_turns: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    FreeSteps,
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::FreeSteps => write!(f, "a step must cost at least 1"),
+        }
+    }
+}
+
+impl Error for RulesError {}
+
+impl Default for ReindeerRules {
+    fn default() -> Self {
+        Self { step_cost: 1, turn_cost: 1000, allow_u_turns: false }
+    }
+}
+
+impl ReindeerRules {
+    pub fn new(step_cost: usize, turn_cost: usize, allow_u_turns: bool) -> Result<Self, RulesError> {
+        if step_cost == 0 {
+            return Err(RulesError::FreeSteps);
+        }
+        Ok(Self { step_cost, turn_cost, allow_u_turns })
+    }
+
+    /// The directions a reindeer facing `direction` can step towards, with the cost of the move
+    fn moves(&self, direction: Direction) -> Vec<(Direction, usize)> {
+        let mut moves = vec![
+            (direction, self.step_cost),
+            (direction.rotate_left(), self.turn_cost + self.step_cost),
+            (direction.rotate_right(), self.turn_cost + self.step_cost),
+        ];
+        if self.allow_u_turns {
+            moves.push((direction.opposite(), 2 * self.turn_cost + self.step_cost));
+        }
+        moves
+    }
+}
+
+/// The optimal paths from the start to the end, as the graph of the moves they are made of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestPaths {
+    pub score: usize,
+    pub start: State,
+    pub end: Point,
+    /// Every state on an optimal path, with the states following it on an optimal path
+    pub next: HashMap<State, Vec<State>>,
+}
+
+impl BestPaths {
+    pub fn tiles(&self) -> HashSet<Point> {
+        self.next.keys().map(|&(position, _)| position).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        let mut counts = HashMap::new();
+        self.count_from(self.start, &mut counts)
+    }
+
+    fn count_from(&self, state: State, counts: &mut HashMap<State, usize>) -> usize {
+        if state.0 == self.end {
+            return 1;
+        }
+        if let Some(&count) = counts.get(&state) {
+            return count;
+        }
+
+        let count = self.next[&state].iter().map(|&next| self.count_from(next, counts)).sum();
+        counts.insert(state, count);
+        count
+    }
+
+    /// Every optimal path from the start to the end, both included. There can be a lot of them,
+    /// `count` tells how many without listing them
+    pub fn paths(&self) -> Vec<Vec<State>> {
+        let mut paths = vec![];
+        let mut to_visit = vec![vec![self.start]];
+        while let Some(path) = to_visit.pop() {
+            let &last = path.last().unwrap();
+            if last.0 == self.end {
+                paths.push(path);
+                continue;
+            }
+
+            for &next in &self.next[&last] {
+                let mut longer = path.clone();
+                longer.push(next);
+                to_visit.push(longer);
+            }
+        }
+        paths
+    }
+}
+
+/// A state is on an optimal path when its best scores from the start and to the end add up to the
+/// best score, so one Dijkstra search runs from the start and another one backwards from the end
+pub fn best_paths(walls: &HashSet<Point>, start: Point, end: Point, rules: &ReindeerRules) -> Option<BestPaths> {
+    let start = (start, Direction::Right);
+    let forward = dijkstra([start], |state| neighbours(walls, rules, state), |_| false).distances;
+    let backward = dijkstra(Direction::ALL.map(|d| (end, d)), |state| predecessors(walls, rules, state), |_| false).distances;
+
+    let score = Direction::ALL.iter().filter_map(|&d| forward.get(&(end, d))).min().copied()?;
+    let on_best_path = |state: &State| {
+        forward.get(state).zip(backward.get(state)).is_some_and(|(f, b)| f + b == score)
+    };
+
+    let next = forward.keys()
+        .filter(|state| on_best_path(state))
+        .map(|&state| {
+            let next = neighbours(walls, rules, &state).into_iter()
+                .filter(|(next, cost)| on_best_path(next) && forward[&state] + cost == forward[next])
+                .map(|(next, _)| next)
+                .collect();
+            (state, next)
+        })
+        .collect();
+
+    Some(BestPaths { score, start, end, next })
+}
+
+fn neighbours(walls: &HashSet<Point>, rules: &ReindeerRules, &(position, direction): &State) -> Vec<(State, usize)> {
+    rules.moves(direction).into_iter()
+        .map(|(direction, cost)| ((position.step(direction), direction), cost))
+        .filter(|((position, _), _)| !walls.contains(position))
+        .collect()
+}
+
+/// The moves of `neighbours` backwards: the states a move leads from to reach this one. Turning
+/// left and right cost the same, so the directions a move can come from are the ones it can go to
+fn predecessors(walls: &HashSet<Point>, rules: &ReindeerRules, &(position, direction): &State) -> Vec<(State, usize)> {
+    let previous = position.step(direction.opposite());
+    if walls.contains(&previous) {
+        return vec![];
+    }
+
+    rules.moves(direction).into_iter()
+        .map(|(direction, cost)| ((previous, direction), cost))
+        .collect()
+}
+
+/// The maze with the tiles on a best path marked 'O', the tiles of `path` drawn with the direction
+/// the reindeer faces when it reaches them
+pub fn render(walls: &HashSet<Point>, best: &BestPaths, path: &[State]) -> String {
+    let mut grid = Grid::new(width(walls), height(walls), '.');
+    for &wall in walls {
+        grid.set(wall, '#');
+    }
+    for tile in best.tiles() {
+        grid.set(tile, 'O');
+    }
+    for &(position, direction) in path {
+        grid.set(position, direction.to_char());
+    }
+    grid.set(best.start.0, 'S');
+    grid.set(best.end, 'E');
+    grid.to_string()
+}
+
+/// Every tile becomes a square of `scale` pixels, the path is drawn over the best tiles
+pub fn render_image(walls: &HashSet<Point>, best: &BestPaths, path: &[State], scale: u32) -> RgbImage {
+    let tiles = best.tiles();
+    let path: HashSet<Point> = path.iter().map(|&(position, _)| position).collect();
+
+    let (width, height) = (width(walls) as u32, height(walls) as u32);
+    RgbImage::from_fn(width * scale, height * scale, |x, y| {
+        let position = Point::new((x / scale) as isize, (y / scale) as isize);
+        let colour = if position == best.start.0 || position == best.end {
+            [220, 40, 40]
+        } else if walls.contains(&position) {
+            [40, 40, 40]
+        } else if path.contains(&position) {
+            [240, 200, 40]
+        } else if tiles.contains(&position) {
+            [80, 160, 240]
+        } else {
+            [255, 255, 255]
+        };
+        Rgb(colour)
+    })
+}
+
+pub fn save_image(walls: &HashSet<Point>, best: &BestPaths, path: &[State], scale: u32, file: &Path) -> ImageResult<()> {
+    render_image(walls, best, path, scale).save(file)
+}
+
+/// The maze is surrounded by walls, they give its size
+fn width(walls: &HashSet<Point>) -> usize {
+    walls.iter().map(|wall| wall.x + 1).max().unwrap_or(0) as usize
+}
+
+fn height(walls: &HashSet<Point>) -> usize {
+    walls.iter().map(|wall| wall.y + 1).max().unwrap_or(0) as usize
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use priority_queue::PriorityQueue;
-    use std::collections::{HashMap, HashSet};
 
     #[test]
     fn it_find_the_lowest_score() {
@@ -25,9 +290,9 @@ mod tests {
         ###############
         "};
 
-        let (walls, start, end) = parse_map(input);
-        assert_eq!(7036, find_lowest_score(&walls, start, end));
-        assert_eq!(45, tiles_on_best_paths(&walls, start, end, 7036).len());
+        let (walls, start, end) = parse_map(input).unwrap();
+        assert_eq!(Some(7036), find_lowest_score(&walls, start, end));
+        assert_eq!(45, tiles_on_best_paths(&walls, start, end).len());
 
         let input = indoc! {"
         #################
@@ -49,146 +314,104 @@ mod tests {
         #################
         "};
 
-        let (walls, start, end) = parse_map(input);
-        assert_eq!(11048, find_lowest_score(&walls, start, end));
-        assert_eq!(64, tiles_on_best_paths(&walls, start, end, 11048).len());
+        let (walls, start, end) = parse_map(input).unwrap();
+        assert_eq!(Some(11048), find_lowest_score(&walls, start, end));
+        assert_eq!(64, tiles_on_best_paths(&walls, start, end).len());
     }
 
     #[test]
-    fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_16");
-        let (walls, start, end) = parse_map(input);
-        assert_eq!(91464, find_lowest_score(&walls, start, end));
-        assert_eq!(494, tiles_on_best_paths(&walls, start, end, 91464).len()); // slow ~ 1 minute
-    }
-
-    fn parse_map(input: &str) -> (HashSet<(isize, isize)>, (isize, isize), (isize, isize)) {
-        let mut walls = HashSet::new();
-        let mut start = (-1, -1);
-        let mut end = (-1, -1);
-        for (y, line) in read_lines(input).iter().enumerate() {
-            for (x, char) in line.chars().enumerate() {
-                if char == '#' {
-                    walls.insert((x as isize, y as isize));
-                }
-
-                if char == 'S' {
-                    start = (x as isize, y as isize)
-                }
-
-                if char == 'E' {
-                    end = (x as isize, y as isize)
-                }
-            }
-        }
-        (walls, start, end)
-    }
-
-    fn find_lowest_score(walls: &HashSet<(isize, isize)>, start: (isize, isize), end: (isize, isize)) -> isize {
-        let mut pq = PriorityQueue::new();
-        pq.push((start, Direction::East, vec![]), 0);
-        let mut final_score = 0;
-
-        let mut visited = HashSet::new();
-        visited.insert((start, Direction::East));
-
-        while let Some(((position, orientation, path), score)) = pq.pop() {
-            if position == end {
-                final_score = score;
-                break;
-            }
+    fn it_enumerates_the_best_paths() {
+        let input = indoc! {"
+        #######
+        #....E#
+        #.#.###
+        #S....#
+        #######
+        "};
 
-            for (p, d, s) in neighbours(&position, &orientation) {
-                if visited.contains(&(p.clone(), d.clone())) || walls.contains(&p) {
-                    continue;
-                }
+        let (walls, start, end) = parse_map(input).unwrap();
+        let best = best_paths(&walls, start, end, &ReindeerRules::default()).unwrap();
+        assert_eq!(2006, best.score);
+        assert_eq!(2, best.count());
 
-                visited.insert((p.clone(), d.clone()));
-                let mut new_path = path.clone();
-                new_path.push((p.clone(), d.clone()));
-                pq.push((p, d, new_path), score + s);
-            }
+        let paths = best.paths();
+        assert_eq!(2, paths.len());
+        for path in &paths {
+            assert_eq!(((start, Direction::Right), end), (path[0], path.last().unwrap().0));
+            let score: usize = path.windows(2)
+                .map(|pair| if pair[0].1 == pair[1].1 { 1 } else { 1001 })
+                .sum();
+            assert_eq!(best.score, score);
         }
 
-        final_score * -1
+        let tiles: HashSet<Point> = paths.iter().flatten().map(|&(position, _)| position).collect();
+        assert_eq!(tiles, best.tiles());
+        assert_eq!(10, tiles.len());
+        assert_eq!(None, best_paths(&walls, start, Point::new(2, 2), &ReindeerRules::default()));
     }
 
-    fn tiles_on_best_paths(walls: &HashSet<(isize, isize)>, start_position: Position, end_position: Position, credit: isize) -> HashSet<Position> {
-        let mut queue = PriorityQueue::new();
-        let current = vec![start_position];
-        queue.push((start_position, Direction::East, current), 0);
-        let mut seen = HashMap::new();
-        let mut best = HashSet::new();
-
-        while let Some(((location, direction, path), score)) = queue.pop() {
-            seen.insert((location, direction), score);
+    #[test]
+    fn it_applies_the_movement_rules() {
+        let (walls, start, end) = parse_map("#######\n#E...S#\n#######").unwrap();
+        let u_turns = ReindeerRules::new(1, 1000, true).unwrap();
+        assert_eq!(None, lowest_score(&walls, start, end, &ReindeerRules::default()));
+        assert_eq!(Some(2004), lowest_score(&walls, start, end, &u_turns));
+        assert_eq!(Some(16), lowest_score(&walls, start, end, &ReindeerRules::new(2, 4, true).unwrap()));
 
-            if location == end_position {
-                best.extend(path);
-                continue;
-            }
+        let (walls, start, end) = parse_map("#######\n#....E#\n#.#.###\n#S....#\n#######").unwrap();
+        let cheap_turns = ReindeerRules::new(1, 1, false).unwrap();
+        let best = best_paths(&walls, start, end, &cheap_turns).unwrap();
+        assert_eq!((8, 2), (best.score, best.count()));
+    }
 
-            for (n, d, cost) in neighbours(&location, &direction) {
-                if walls.contains(&n) {
-                    continue;
-                }
+    #[test]
+    fn it_rejects_free_steps() {
+        assert_eq!(Err(RulesError::FreeSteps), ReindeerRules::new(0, 1000, false));
+        assert_eq!(Err(RulesError::FreeSteps), ReindeerRules::new(0, 0, true));
+        assert!(ReindeerRules::new(1, 0, true).is_ok());
+    }
 
-                if -(score + cost) > credit {
-                    continue;
-                }
+    #[test]
+    fn it_renders_the_best_paths() {
+        let (walls, start, end) = parse_map("#######\n#....E#\n#.#.###\n#S....#\n#######").unwrap();
+        let best = best_paths(&walls, start, end, &ReindeerRules::default()).unwrap();
+        let path = best.paths().into_iter()
+            .find(|path| path[1].0 == Point::new(2, 3))
+            .unwrap();
 
-                if *seen.get(&(n, d)).unwrap_or(&isize::MIN) > (score + cost) {
-                    continue;
-                }
+        assert_eq!(
+            indoc! {"
+            #######
+            #OO^>E#
+            #O#^###
+            #S>>..#
+            #######"},
+            render(&walls, &best, &path)
+        );
 
-                let mut new_path = path.clone();
-                new_path.push(n);
-                queue.push((n, d, new_path), score + cost);
-            }
-        }
-
-        best
+        let image = render_image(&walls, &best, &path, 2);
+        assert_eq!((14, 10), image.dimensions());
+        assert_eq!(
+            [[40, 40, 40], [220, 40, 40], [240, 200, 40], [80, 160, 240], [255, 255, 255]],
+            [(0, 0), (2, 6), (6, 2), (2, 2), (8, 6)].map(|(x, y)| image.get_pixel(x, y).0)
+        );
     }
 
-    type Position = (isize, isize);
+    #[test]
+    fn it_reports_invalid_mazes() {
+        assert_eq!(Some(ParseError::new(16, 2, 3, "'#', '.', 'S' or 'E'")), parse_map("#####\n#S?E#\n#####").err());
+        assert_eq!(Some(ParseError::new(16, 1, 1, "an end tile 'E'")), parse_map("#####\n#S..#\n#####").err());
 
-    #[derive(Debug, Clone, Hash, Eq, PartialEq, Copy)]
-    enum Direction {
-        North,
-        South,
-        East,
-        West,
+        let walled_in = parse_map("#####\n#S#E#\n#####").unwrap();
+        assert_eq!(Answer::Error(UNREACHABLE_END.to_string()), Day16.part1(&walled_in));
+        assert_eq!(Answer::Error(UNREACHABLE_END.to_string()), Day16.part2(&walled_in));
     }
 
-    fn neighbours((x, y): &Position, direction: &Direction) -> Vec<(Position, Direction, isize)> {
-        if *direction == Direction::East {
-            return vec![
-                ((x + 1, *y), Direction::East, -1),
-                ((*x, y - 1), Direction::North, -1001),
-                ((*x, y + 1), Direction::South, -1001),
-            ];
-        }
-
-        if *direction == Direction::West {
-            return vec![
-                ((x - 1, *y), Direction::West, -1),
-                ((*x, y - 1), Direction::North, -1001),
-                ((*x, y + 1), Direction::South, -1001),
-            ];
-        }
-
-        if *direction == Direction::North {
-            return vec![
-                ((*x, y - 1), Direction::North, -1),
-                ((x - 1, *y), Direction::West, -1001),
-                ((x + 1, *y), Direction::East, -1001),
-            ];
-        }
-
-        vec![
-            ((*x, y + 1), Direction::South, -1),
-            ((x - 1, *y), Direction::West, -1001),
-            ((x + 1, *y), Direction::East, -1001),
-        ]
+    #[test]
+    fn it_solves_first_puzzle() {
+        let input = &personal_input!("input_16");
+        let (walls, start, end) = parse_map(input).unwrap();
+        assert_eq!(Some(91464), find_lowest_score(&walls, start, end));
+        assert_eq!(494, tiles_on_best_paths(&walls, start, end).len());
     }
-}
\ No newline at end of file
+}
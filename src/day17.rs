@@ -1,291 +1,390 @@
-#[cfg(test)]
-mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
-    use indoc::indoc;
-    use itertools::Itertools;
-    use std::collections::{HashMap, VecDeque};
-    use std::ops::BitXor;
-    use winnow::stream::ToUsize;
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use itertools::Itertools;
+use quine::QuineError;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
 
-    #[test]
-    fn it_execute_instructions() {
-        let mut machine = Machine::new(0, 0, 9);
-        Bst {}.execute(&mut machine, 6);
-        assert_eq!(1, machine.read_registry('B'));
+pub mod assembly;
+pub mod debugger;
+pub mod quine;
 
-        let mut machine = Machine::new(0, 29, 0);
-        Bxl {}.execute(&mut machine, 7);
-        assert_eq!(26, machine.read_registry('B'));
+pub struct Day17;
 
-        Out {}.execute(&mut machine, 1);
-        assert_eq!(vec![1], machine.stdout);
+impl Solver for Day17 {
+    type Input = Program;
 
-        let mut machine = Machine::new(2024, 0, 0);
-        machine.execute_instructions(vec![0, 1, 5, 4, 3, 0]);
-        assert_eq!("4,2,5,6,7,7,7,7,3,1,0", machine.output());
-        assert_eq!(0, machine.read_registry('A'));
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_program(input)
+    }
 
-        let mut machine = Machine::new(729, 0, 0);
-        machine.execute_instructions(vec![0, 1, 5, 4, 3, 0]);
-        assert_eq!("4,6,3,5,6,3,5,2,1,0", machine.output());
+    fn part1(&self, program: &Self::Input) -> Answer {
+        run(program).into()
     }
 
-    #[test]
-    fn it_parses_input() {
-        let input = indoc! {"
-        Register A: 729
-        Register B: 0
-        Register C: 0
+    fn part2(&self, program: &Self::Input) -> Answer {
+        find_program_copy(program).into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub register_a: u64,
+    pub register_b: u64,
+    pub register_c: u64,
+    pub instructions: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Opcode {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Opcode {
+    /// Ordered by code
+    pub const ALL: [Opcode; 8] = [
+        Opcode::Adv, Opcode::Bxl, Opcode::Bst, Opcode::Jnz, Opcode::Bxc, Opcode::Out, Opcode::Bdv, Opcode::Cdv,
+    ];
+
+    pub fn from_code(code: usize) -> Option<Self> {
+        Self::ALL.get(code).copied()
+    }
 
-        Program: 0,1,5,4,3,0
-        "};
+    pub fn code(self) -> usize {
+        self as usize
+    }
 
-        assert_eq!("4,6,3,5,6,3,5,2,1,0", execute_program(input));
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|opcode| opcode.mnemonic() == mnemonic)
     }
 
-    #[test]
-    fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_day17");
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Adv => "adv",
+            Opcode::Bxl => "bxl",
+            Opcode::Bst => "bst",
+            Opcode::Jnz => "jnz",
+            Opcode::Bxc => "bxc",
+            Opcode::Out => "out",
+            Opcode::Bdv => "bdv",
+            Opcode::Cdv => "cdv",
+        }
+    }
 
-        assert_eq!("6,5,4,7,1,6,0,3,1", execute_program(input));
+    /// Combo operands 4 to 6 read the registers A to C, the other ops use their operand as is
+    pub fn takes_combo_operand(self) -> bool {
+        matches!(self, Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv)
     }
+}
 
-    #[test]
-    fn it_finds_register_a_value_generating_program_copy() {
-        let input = indoc! {"
-        Register A: 2024
-        Register B: 0
-        Register C: 0
+pub fn parse_program(input: &str) -> Result<Program, ParseError> {
+    let mut lines = lines(17, input);
 
-        Program: 0,3,5,4,3,0
-        "};
+    let register_a = parse_register(next_line(&mut lines, 1)?, "Register A: ")?;
+    let register_b = parse_register(next_line(&mut lines, 2)?, "Register B: ")?;
+    let register_c = parse_register(next_line(&mut lines, 3)?, "Register C: ")?;
 
-        assert_eq!(117440, find_register_a_value_generating_program_copy(input));
+    let separator = next_line(&mut lines, 4)?;
+    if !separator.text.is_empty() {
+        return Err(separator.error_at(separator.text, "an empty line"));
     }
 
-    #[test]
-    fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_day17");
-
-        assert_eq!(106086382266778, find_register_a_value_generating_program_copy(input));
+    let line = next_line(&mut lines, 5)?;
+    let instructions = line.strip_prefix("Program: ")?
+        .split(",")
+        .map(|v| match line.parse::<usize>(v, "a 3-bit number")? {
+            code @ 0..=7 => Ok(code),
+            _ => Err(line.error_at(v, "a 3-bit number")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Program { register_a, register_b, register_c, instructions })
+}
+
+fn next_line<'a>(lines: &mut impl Iterator<Item = Line<'a>>, number: usize) -> Result<Line<'a>, ParseError> {
+    lines.next()
+        .ok_or(ParseError::new(17, number, 1, "more lines"))
+}
+
+fn parse_register(line: Line, prefix: &str) -> Result<u64, ParseError> {
+    line.parse::<u64>(line.strip_prefix(prefix)?, "a register value")
+}
+
+pub fn find_register_a_value_generating_program_copy(input: &str) -> Result<u64, QuineError> {
+    find_program_copy(&parse_program(input)?)
+}
+
+fn find_program_copy(program: &Program) -> Result<u64, QuineError> {
+    quine::smallest_register_a(program, &program.instructions)
+}
+
+pub fn execute_program(input: &str) -> Result<String, MachineError> {
+    run(&parse_program(input)?)
+}
+
+fn run(program: &Program) -> Result<String, MachineError> {
+    let mut machine = Machine::new(program.register_a, program.register_b, program.register_c);
+    machine.execute_instructions(&program.instructions, STEP_LIMIT)?;
+    Ok(machine.output())
+}
+
+/// Far more than any puzzle program needs, it only stops programs that never halt
+const STEP_LIMIT: usize = 1_000_000;
+
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+
+/// Invalid opcodes or operands, overflowing divisors and programs that never halt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MachineError {
+    Parse(ParseError),
+    InvalidOpcode { address: usize, code: usize },
+    InvalidOperand { address: usize, operand: usize },
+    Overflow { address: usize },
+    StepLimit(usize),
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MachineError::Parse(error) => write!(f, "{}", error),
+            MachineError::InvalidOpcode { address, code } => write!(f, "invalid opcode {} at {}", code, address),
+            MachineError::InvalidOperand { address, operand } => write!(f, "invalid operand {} at {}", operand, address),
+            MachineError::Overflow { address } => write!(f, "the divisor overflows a register at {}", address),
+            MachineError::StepLimit(limit) => write!(f, "the program did not halt after {} steps", limit),
+        }
     }
+}
 
-    fn find_register_a_value_generating_program_copy(input: &str) -> usize {
-        let lines = read_lines(input);
-        let register_b = lines[1].replace("Register B: ", "").parse::<usize>().unwrap();
-        let register_c = lines[2].replace("Register C: ", "").parse::<usize>().unwrap();
+impl Error for MachineError {}
 
-        let instructions: Vec<_> = lines[4]
-            .replace("Program: ", "")
-            .split(",")
-            .map(|v| v.parse::<usize>().unwrap())
-            .collect();
-
-        let mut machine = Machine::new(0, register_b, register_c);
+impl From<ParseError> for MachineError {
+    fn from(error: ParseError) -> Self {
+        MachineError::Parse(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Machine {
+    registers: [u64; 3],
+    stdout: Vec<usize>,
+    instruction_pointer: usize,
+}
+
+impl Machine {
+    fn new(register_a: u64, register_b: u64, register_c: u64) -> Self {
+        Self {
+            registers: [register_a, register_b, register_c],
+            stdout: vec![],
+            instruction_pointer: 0,
+        }
+    }
 
-        let mut queue = VecDeque::new();
-        queue.push_back((0, instructions.len() - 1));
+    fn execute_instructions(&mut self, instructions: &[usize], step_limit: usize) -> Result<(), MachineError> {
+        let mut steps = 0;
+        while !self.is_halted(instructions) {
+            if steps == step_limit {
+                return Err(MachineError::StepLimit(step_limit));
+            }
+            self.step(instructions)?;
+            steps += 1;
+        }
+        Ok(())
+    }
 
-        while let Some((register_a, instruction_index)) = queue.pop_front() {
-            for i in 0..8 {
-                machine.reset();
-                let new_register_a = 8 * register_a + i;
-                machine.write_to_registry('A', new_register_a);
-                machine.write_to_registry('B', 0);
-                machine.write_to_registry('C', 0);
+    /// The machine halts when the instruction pointer no longer points to an opcode and its operand
+    fn is_halted(&self, instructions: &[usize]) -> bool {
+        self.instruction_pointer + 1 >= instructions.len()
+    }
 
-                machine.execute_instructions(instructions.clone());
+    fn step(&mut self, instructions: &[usize]) -> Result<(), MachineError> {
+        let (opcode, operand) = self.fetch(instructions)?;
+        self.execute(opcode, operand)
+    }
 
-                if machine.stdout == instructions {
-                    return new_register_a;
-                }
+    fn fetch(&self, instructions: &[usize]) -> Result<(Opcode, usize), MachineError> {
+        let address = self.instruction_pointer;
+        let code = instructions[address];
+        let opcode = Opcode::from_code(code).ok_or(MachineError::InvalidOpcode { address, code })?;
+        Ok((opcode, instructions[address + 1]))
+    }
 
-                if machine.stdout[0] == instructions[instruction_index] {
-                    queue.push_back((new_register_a, instruction_index - 1));
-                }
+    fn execute(&mut self, opcode: Opcode, operand: usize) -> Result<(), MachineError> {
+        match opcode {
+            Opcode::Adv => self.registers[A] = self.divide(operand)?,
+            Opcode::Bxl => self.registers[B] ^= self.literal(operand)?,
+            Opcode::Bst => self.registers[B] = self.combo(operand)? % 8,
+            Opcode::Jnz if self.registers[A] != 0 => {
+                self.instruction_pointer = self.literal(operand)? as usize;
+                return Ok(());
             }
+            Opcode::Jnz => {}
+            Opcode::Bxc => self.registers[B] ^= self.registers[C],
+            Opcode::Out => {
+                let value = self.combo(operand)? % 8;
+                self.stdout.push(value as usize);
+            }
+            Opcode::Bdv => self.registers[B] = self.divide(operand)?,
+            Opcode::Cdv => self.registers[C] = self.divide(operand)?,
         }
 
-        panic!("it should always find a solution")
+        self.instruction_pointer += 2;
+        Ok(())
     }
 
-    fn execute_program(input: &str) -> String {
-        let lines = read_lines(input);
-        let register_a = lines[0].replace("Register A: ", "").parse::<usize>().unwrap();
-        let register_b = lines[1].replace("Register B: ", "").parse::<usize>().unwrap();
-        let register_c = lines[2].replace("Register C: ", "").parse::<usize>().unwrap();
-
-        let instruction = lines[4]
-            .replace("Program: ", "")
-            .split(",")
-            .map(|v| v.parse::<usize>().unwrap())
-            .collect();
-
-        let mut machine = Machine::new(register_a, register_b, register_c);
-        machine.execute_instructions(instruction);
-        machine.output()
+    fn literal(&self, operand: usize) -> Result<u64, MachineError> {
+        match operand {
+            0..=7 => Ok(operand as u64),
+            _ => Err(self.invalid_operand(operand)),
+        }
     }
 
-    // Op 0,6,7
-    struct Dv {
-        registry: char,
+    /// Operands 0 to 3 are literals and 4 to 6 read the registers A to C, 7 is reserved
+    fn combo(&self, operand: usize) -> Result<u64, MachineError> {
+        match operand {
+            0..=3 => Ok(operand as u64),
+            4..=6 => Ok(self.registers[operand - 4]),
+            _ => Err(self.invalid_operand(operand)),
+        }
     }
 
-    impl Op for Dv {
-        fn execute(&self, machine: &mut Machine, operand: usize) {
-            let operand_value = match operand {
-                1..=3 => operand,
-                4 => machine.read_registry('A'),
-                5 => machine.read_registry('B'),
-                6 => machine.read_registry('C'),
-                _ => panic!("Invalid program")
-            };
-            let value = machine.read_registry('A') as isize / 2_isize.pow(operand_value as u32);
-            machine.write_to_registry(self.registry, value as usize);
-        }
+    /// A divided by 2 to the power of the combo operand, truncated
+    fn divide(&self, operand: usize) -> Result<u64, MachineError> {
+        let overflow = MachineError::Overflow { address: self.instruction_pointer };
+        let exponent = u32::try_from(self.combo(operand)?).map_err(|_| overflow.clone())?;
+        let divisor = 2_u64.checked_pow(exponent).ok_or(overflow)?;
+        Ok(self.registers[A] / divisor)
     }
 
-    // Op 1
-    struct Bxl;
+    fn invalid_operand(&self, operand: usize) -> MachineError {
+        MachineError::InvalidOperand { address: self.instruction_pointer, operand }
+    }
 
-    impl Op for Bxl {
-        fn execute(&self, machine: &mut Machine, operand: usize) {
-            let value = machine.read_registry('B');
-            machine.write_to_registry('B', value.bitxor(operand));
-        }
+    fn output(&self) -> String {
+        self.stdout.iter().join(",")
     }
+}
 
-    // Op 2
-    struct Bst;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personal_input;
+    use indoc::indoc;
 
-    impl Op for Bst {
-        fn execute(&self, machine: &mut Machine, operand: usize) {
-            let value = match operand {
-                1..=3 => operand,
-                4 => machine.read_registry('A'),
-                5 => machine.read_registry('B'),
-                6 => machine.read_registry('C'),
-                _ => panic!("Invalid program")
-            };
+    #[test]
+    fn it_execute_instructions() {
+        let mut machine = Machine::new(0, 0, 9);
+        machine.execute(Opcode::Bst, 6).unwrap();
+        assert_eq!(1, machine.registers[B]);
 
-            machine.write_to_registry('B', value % 8);
-        }
-    }
+        let mut machine = Machine::new(0, 29, 0);
+        machine.execute(Opcode::Bxl, 7).unwrap();
+        assert_eq!(26, machine.registers[B]);
 
-    // Op 3
-    struct Jnz;
+        machine.execute(Opcode::Out, 1).unwrap();
+        assert_eq!(vec![1], machine.stdout);
 
-    impl Op for Jnz {
-        fn execute(&self, _machine: &mut Machine, _operand: usize) {}
+        let mut machine = Machine::new(2024, 0, 0);
+        machine.execute_instructions(&[0, 1, 5, 4, 3, 0], STEP_LIMIT).unwrap();
+        assert_eq!("4,2,5,6,7,7,7,7,3,1,0", machine.output());
+        assert_eq!(0, machine.registers[A]);
 
-        fn next_instruction(&self, machine: &mut Machine, operand: usize) {
-            let value = machine.read_registry('A');
-            if value == 0 {
-                machine.instruction_pointer += 2;
-                return;
-            }
-            machine.instruction_pointer = operand;
-        }
+        let mut machine = Machine::new(729, 0, 0);
+        machine.execute_instructions(&[0, 1, 5, 4, 3, 0], STEP_LIMIT).unwrap();
+        assert_eq!("4,6,3,5,6,3,5,2,1,0", machine.output());
     }
 
-    // Op 4
-    struct Bxc;
-
-    impl Op for Bxc {
-        fn execute(&self, machine: &mut Machine, _operand: usize) {
-            let value = machine.read_registry('B');
-            let operand = machine.read_registry('C');
-            machine.write_to_registry('B', value.bitxor(operand).to_usize());
-        }
+    #[test]
+    fn it_reports_machine_errors_instead_of_panicking() {
+        let mut machine = Machine::new(u64::MAX, 0, 64);
+        assert_eq!(Err(MachineError::InvalidOperand { address: 0, operand: 7 }), machine.execute(Opcode::Out, 7));
+        assert_eq!(Err(MachineError::Overflow { address: 0 }), machine.execute(Opcode::Adv, 6));
+        assert_eq!(Err(MachineError::Overflow { address: 0 }), machine.execute(Opcode::Adv, 4));
+        assert_eq!(Ok(()), machine.execute(Opcode::Cdv, 0));
+        assert_eq!(u64::MAX, machine.registers[C]);
+
+        let mut machine = Machine::new(1, 0, 0);
+        assert_eq!(Err(MachineError::StepLimit(100)), machine.execute_instructions(&[3, 0], 100));
+        assert_eq!(
+            Err(MachineError::InvalidOpcode { address: 2, code: 8 }),
+            Machine::new(1, 0, 0).execute_instructions(&[5, 0, 8, 0], STEP_LIMIT)
+        );
     }
 
-    // Op 5
-    struct Out;
-
-    impl Op for Out {
-        fn execute(&self, machine: &mut Machine, operand: usize) {
-            let operand_value = match operand {
-                1..=3 => operand,
-                4 => machine.read_registry('A'),
-                5 => machine.read_registry('B'),
-                6 => machine.read_registry('C'),
-                _ => panic!("Invalid program")
-            };
-            machine.write_to_stdout(operand_value % 8);
-        }
+    #[test]
+    fn it_answers_with_the_machine_error() {
+        let program = parse_program("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 5,7").unwrap();
+
+        assert_eq!(Answer::Error("invalid operand 7 at 0".to_string()), Day17.part1(&program));
     }
-    #[derive(Debug)]
-    struct Machine {
-        registries: HashMap<char, usize>,
-        stdout: Vec<usize>,
-        instruction_pointer: usize,
+
+    #[test]
+    fn it_parses_input() {
+        let input = indoc! {"
+        Register A: 729
+        Register B: 0
+        Register C: 0
+
+        Program: 0,1,5,4,3,0
+        "};
+
+        assert_eq!(Ok("4,6,3,5,6,3,5,2,1,0".to_string()), execute_program(input));
     }
 
-    impl Machine {
-        fn new(registry_a: usize, registry_b: usize, registry_c: usize) -> Self {
-            let mut registries = HashMap::new();
-            registries.insert('A', registry_a);
-            registries.insert('B', registry_b);
-            registries.insert('C', registry_c);
-
-            Self {
-                registries,
-                stdout: vec![],
-                instruction_pointer: 0,
-            }
-        }
+    #[test]
+    fn it_reports_invalid_programs() {
+        let input = indoc! {"
+        Register A: 729
+        Register B: x
+        "};
+        assert_eq!(Some(ParseError::new(17, 2, 13, "a register value")), parse_program(input).err());
 
-        fn reset(&mut self) {
-            self.instruction_pointer = 0;
-            self.stdout.clear();
-        }
+        let input = indoc! {"
+        Register A: 729
+        Register B: 0
+        Register C: 0
 
-        fn execute_instructions(&mut self, instruction: Vec<usize>) {
-            while self.instruction_pointer < instruction.len() - 1 {
-                let code = instruction[self.instruction_pointer];
-                let operand = instruction[self.instruction_pointer + 1];
-                let instruction = Self::decode_instruction(code);
-                instruction.execute(self, operand);
-                instruction.next_instruction(self, operand);
-            }
-        }
+        Program: 0,1,8,4,3,0
+        "};
+        assert_eq!(Some(ParseError::new(17, 5, 14, "a 3-bit number")), parse_program(input).err());
 
-        fn decode_instruction(code: usize) -> Box<dyn Op> {
-            match code {
-                0 => Box::new(Dv { registry: 'A' }),
-                1 => Box::new(Bxl {}),
-                2 => Box::new(Bst {}),
-                3 => Box::new(Jnz {}),
-                4 => Box::new(Bxc {}),
-                5 => Box::new(Out {}),
-                6 => Box::new(Dv { registry: 'B' }),
-                7 => Box::new(Dv { registry: 'C' }),
-                _ => panic!("unknown instruction for code {}", code)
-            }
-        }
+        assert_eq!(Some(ParseError::new(17, 2, 1, "more lines")), parse_program("Register A: 729").err());
+    }
 
-        fn output(&self) -> String {
-            Itertools::join(&mut self.stdout.iter(), ",")
-        }
+    #[test]
+    fn it_solves_first_puzzle() {
+        let input = &personal_input!("input_day17");
 
-        fn read_registry(&self, registry: char) -> usize {
-            *self.registries.get(&registry).unwrap()
-        }
+        assert_eq!(Ok("6,5,4,7,1,6,0,3,1".to_string()), execute_program(input));
+    }
 
-        fn write_to_registry(&mut self, registry: char, value: usize) {
-            self.registries.insert(registry, value);
-        }
+    #[test]
+    fn it_finds_register_a_value_generating_program_copy() {
+        let input = indoc! {"
+        Register A: 2024
+        Register B: 0
+        Register C: 0
 
-        fn write_to_stdout(&mut self, value: usize) {
-            self.stdout.push(value)
-        }
+        Program: 0,3,5,4,3,0
+        "};
+
+        assert_eq!(Ok(117440), find_register_a_value_generating_program_copy(input));
     }
 
-    trait Op {
-        fn execute(&self, machine: &mut Machine, operand_code: usize);
+    #[test]
+    fn it_solves_second_puzzle() {
+        let input = &personal_input!("input_day17");
 
-        fn next_instruction(&self, machine: &mut Machine, _operand: usize) {
-            machine.instruction_pointer += 2
-        }
+        assert_eq!(Ok(106086382266778), find_register_a_value_generating_program_copy(input));
     }
-}
\ No newline at end of file
+}
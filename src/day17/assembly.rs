@@ -0,0 +1,112 @@
+use super::Opcode;
+use crate::parse_error::{lines, Line, ParseError};
+use itertools::Itertools;
+
+/// One instruction per line, e.g. `bst A` or `jnz 0`. A trailing opcode without operand is listed
+/// alone and codes that are not 3-bit numbers are listed as is
+pub fn disassemble(instructions: &[usize]) -> String {
+    instructions.chunks(2)
+        .map(|chunk| match (Opcode::from_code(chunk[0]), chunk.get(1)) {
+            (Some(opcode), Some(&operand)) => format!("{} {}", opcode.mnemonic(), operand_name(opcode, operand)),
+            (Some(opcode), None) => opcode.mnemonic().to_string(),
+            (None, _) => chunk.iter().join(" "),
+        })
+        .join("\n")
+}
+
+/// Combo operand 7 is reserved, it is listed as a number so that any program can be read back
+fn operand_name(opcode: Opcode, operand: usize) -> String {
+    match operand {
+        4..=6 if opcode.takes_combo_operand() => ["A", "B", "C"][operand - 4].to_string(),
+        _ => operand.to_string(),
+    }
+}
+
+/// Reads a listing written by `disassemble` back into the program, empty lines are skipped
+pub fn assemble(listing: &str) -> Result<Vec<usize>, ParseError> {
+    let lines: Vec<_> = lines(17, listing)
+        .filter(|line| !line.text.trim().is_empty())
+        .collect();
+
+    let mut instructions = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        let mut tokens = line.text.split_whitespace();
+
+        let mnemonic = tokens.next().unwrap();
+        let opcode = Opcode::from_mnemonic(mnemonic)
+            .ok_or_else(|| line.error_at(mnemonic, "a mnemonic"))?;
+        instructions.push(opcode.code());
+
+        match tokens.next() {
+            Some(operand) => instructions.push(parse_operand(line, opcode, operand)?),
+            None if index == lines.len() - 1 => {}
+            None => return Err(line.error_at_end("an operand")),
+        }
+
+        if let Some(token) = tokens.next() {
+            return Err(line.error_at(token, "the end of the line"));
+        }
+    }
+
+    Ok(instructions)
+}
+
+fn parse_operand(line: &Line, opcode: Opcode, operand: &str) -> Result<usize, ParseError> {
+    if !opcode.takes_combo_operand() {
+        return match line.parse::<usize>(operand, "a 3-bit number")? {
+            value @ 0..=7 => Ok(value),
+            _ => Err(line.error_at(operand, "a 3-bit number")),
+        };
+    }
+
+    match operand {
+        "A" => Ok(4),
+        "B" => Ok(5),
+        "C" => Ok(6),
+        "0" | "1" | "2" | "3" | "7" => Ok(operand.parse().unwrap()),
+        _ => Err(line.error_at(operand, "a combo operand (0 to 3, A, B or C)")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn it_disassembles_programs() {
+        assert_eq!("adv 1\nout A\njnz 0", disassemble(&[0, 1, 5, 4, 3, 0]));
+        assert_eq!(
+            indoc! {"
+            bst A
+            bxl 3
+            cdv B
+            bxc 1
+            adv 3
+            bxl 5
+            out B
+            jnz 0"},
+            disassemble(&[2, 4, 1, 3, 7, 5, 4, 1, 0, 3, 1, 5, 5, 5, 3, 0])
+        );
+        assert_eq!("bdv 7\nbxl 6\nout", disassemble(&[6, 7, 1, 6, 5]));
+    }
+
+    #[test]
+    fn it_assembles_listings() {
+        let program = vec![2, 4, 1, 3, 7, 5, 4, 1, 0, 3, 1, 5, 5, 5, 3, 0];
+
+        assert_eq!(Ok(program.clone()), assemble(&disassemble(&program)));
+        assert_eq!(Ok(vec![0, 3, 5, 4, 3, 0]), assemble("adv 3\n\n  out   A\njnz 0\n"));
+        assert_eq!(Ok(vec![6, 7, 1, 6, 5]), assemble("bdv 7\nbxl 6\nout"));
+    }
+
+    #[test]
+    fn it_reports_invalid_listings() {
+        assert_eq!(Err(ParseError::new(17, 2, 1, "a mnemonic")), assemble("adv 3\nmul 2"));
+        assert_eq!(Err(ParseError::new(17, 1, 5, "a combo operand (0 to 3, A, B or C)")), assemble("bst 4"));
+        assert_eq!(Err(ParseError::new(17, 1, 5, "a 3-bit number")), assemble("bxl 8"));
+        assert_eq!(Err(ParseError::new(17, 1, 5, "a 3-bit number")), assemble("jnz A"));
+        assert_eq!(Err(ParseError::new(17, 1, 4, "an operand")), assemble("out\njnz 0"));
+        assert_eq!(Err(ParseError::new(17, 1, 7, "the end of the line")), assemble("out A B"));
+    }
+}
@@ -0,0 +1,246 @@
+use super::{Machine, MachineError, Opcode, Program};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Breakpoint {
+    /// Pauses before executing the instruction at this address
+    InstructionPointer(usize),
+    /// Pauses as soon as the program has printed this many values
+    OutputCount(usize),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugOptions {
+    pub breakpoints: Vec<Breakpoint>,
+    pub step_limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Breakpoint(Breakpoint),
+    StepLimit,
+}
+
+/// Registers are listed in the A, B, C order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub step: usize,
+    pub instruction_pointer: usize,
+    pub opcode: Opcode,
+    pub operand: usize,
+    pub registers_before: [u64; 3],
+    pub registers_after: [u64; 3],
+    pub output: Option<usize>,
+}
+
+/// Runs a program one instruction at a time and records every step
+#[derive(Debug)]
+pub struct Debugger {
+    machine: Machine,
+    instructions: Vec<usize>,
+    options: DebugOptions,
+    trace: Vec<TraceStep>,
+    /// The number of steps taken when run last paused on a breakpoint
+    paused_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(program: &Program, options: DebugOptions) -> Self {
+        Self {
+            machine: Machine::new(program.register_a, program.register_b, program.register_c),
+            instructions: program.instructions.clone(),
+            options,
+            trace: vec![],
+            paused_at: None,
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.machine.is_halted(&self.instructions)
+    }
+
+    pub fn instruction_pointer(&self) -> usize {
+        self.machine.instruction_pointer
+    }
+
+    pub fn registers(&self) -> [u64; 3] {
+        self.machine.registers
+    }
+
+    pub fn output(&self) -> String {
+        self.machine.output()
+    }
+
+    pub fn trace(&self) -> &[TraceStep] {
+        &self.trace
+    }
+
+    /// Executes a single instruction whatever the breakpoints and the step limit, None once halted
+    pub fn step(&mut self) -> Result<Option<&TraceStep>, MachineError> {
+        if self.is_halted() {
+            return Ok(None);
+        }
+
+        let instruction_pointer = self.machine.instruction_pointer;
+        let (opcode, operand) = self.machine.fetch(&self.instructions)?;
+        let registers_before = self.machine.registers;
+        let output_count = self.machine.stdout.len();
+
+        self.machine.execute(opcode, operand)?;
+
+        self.trace.push(TraceStep {
+            step: self.trace.len() + 1,
+            instruction_pointer,
+            opcode,
+            operand,
+            registers_before,
+            registers_after: self.machine.registers,
+            output: self.machine.stdout.get(output_count).copied(),
+        });
+        Ok(self.trace.last())
+    }
+
+    /// Breakpoints are checked before every instruction but the one run is paused on, so calling run
+    /// again resumes after the breakpoint. A failing instruction leaves the machine paused on it.
+    pub fn run(&mut self) -> Result<Stop, MachineError> {
+        let mut resuming = self.paused_at.take() == Some(self.trace.len());
+        loop {
+            if !resuming {
+                if let Some(&breakpoint) = self.options.breakpoints.iter().find(|&&b| self.hits(b)) {
+                    self.paused_at = Some(self.trace.len());
+                    return Ok(Stop::Breakpoint(breakpoint));
+                }
+            }
+            resuming = false;
+
+            if self.is_halted() {
+                return Ok(Stop::Halted);
+            }
+            if self.options.step_limit.is_some_and(|limit| self.trace.len() >= limit) {
+                return Ok(Stop::StepLimit);
+            }
+
+            self.step()?;
+        }
+    }
+
+    fn hits(&self, breakpoint: Breakpoint) -> bool {
+        match breakpoint {
+            Breakpoint::InstructionPointer(address) => self.machine.instruction_pointer == address,
+            Breakpoint::OutputCount(count) => {
+                self.trace.last().is_some_and(|step| step.output.is_some()) && self.machine.stdout.len() == count
+            }
+        }
+    }
+}
+
+/// One JSON object per line so that two runs can be compared with a line based diff
+pub fn to_json_lines(trace: &[TraceStep]) -> String {
+    trace.iter()
+        .map(|step| serde_json::to_string(step).expect("trace steps are always serializable"))
+        .join("\n")
+}
+
+pub fn from_json_lines(json_lines: &str) -> Result<Vec<TraceStep>, String> {
+    json_lines.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| e.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(register_a: u64, instructions: Vec<usize>) -> Program {
+        Program { register_a, register_b: 0, register_c: 0, instructions }
+    }
+
+    #[test]
+    fn it_traces_every_step() {
+        let mut debugger = Debugger::new(&program(729, vec![0, 1, 5, 4, 3, 0]), DebugOptions::default());
+
+        assert_eq!(Ok(Stop::Halted), debugger.run());
+        assert_eq!("4,6,3,5,6,3,5,2,1,0", debugger.output());
+        assert_eq!(30, debugger.trace().len());
+        assert_eq!(
+            TraceStep {
+                step: 2,
+                instruction_pointer: 2,
+                opcode: Opcode::Out,
+                operand: 4,
+                registers_before: [364, 0, 0],
+                registers_after: [364, 0, 0],
+                output: Some(4),
+            },
+            debugger.trace()[1]
+        );
+        assert_eq!((Opcode::Jnz, None), (debugger.trace()[2].opcode, debugger.trace()[2].output));
+        assert_eq!(Ok(None), debugger.step());
+    }
+
+    #[test]
+    fn it_pauses_on_breakpoints() {
+        let options = DebugOptions {
+            breakpoints: vec![Breakpoint::OutputCount(3), Breakpoint::InstructionPointer(4)],
+            step_limit: None,
+        };
+        let mut debugger = Debugger::new(&program(729, vec![0, 1, 5, 4, 3, 0]), options);
+
+        assert_eq!(Ok(Stop::Breakpoint(Breakpoint::InstructionPointer(4))), debugger.run());
+        assert_eq!((2, 4, "4".to_string()), (debugger.trace().len(), debugger.instruction_pointer(), debugger.output()));
+
+        assert_eq!(Ok(Stop::Breakpoint(Breakpoint::InstructionPointer(4))), debugger.run());
+        assert_eq!(5, debugger.trace().len());
+
+        assert_eq!(Ok(Stop::Breakpoint(Breakpoint::OutputCount(3))), debugger.run());
+        assert_eq!((8, "4,6,3".to_string()), (debugger.trace().len(), debugger.output()));
+        assert_eq!([91, 0, 0], debugger.registers());
+    }
+
+    #[test]
+    fn it_pauses_before_the_first_instruction() {
+        let options = DebugOptions { breakpoints: vec![Breakpoint::InstructionPointer(0)], step_limit: None };
+        let mut debugger = Debugger::new(&program(729, vec![0, 1, 5, 4, 3, 0]), options);
+
+        assert_eq!(Ok(Stop::Breakpoint(Breakpoint::InstructionPointer(0))), debugger.run());
+        assert_eq!((0, 0), (debugger.trace().len(), debugger.instruction_pointer()));
+
+        assert_eq!(Ok(Stop::Breakpoint(Breakpoint::InstructionPointer(0))), debugger.run());
+        assert_eq!((3, 0), (debugger.trace().len(), debugger.instruction_pointer()));
+
+        debugger.step().unwrap();
+        debugger.step().unwrap();
+        debugger.step().unwrap();
+        assert_eq!(Ok(Stop::Breakpoint(Breakpoint::InstructionPointer(0))), debugger.run());
+        assert_eq!(6, debugger.trace().len());
+    }
+
+    #[test]
+    fn it_stops_infinite_loops_at_the_step_limit() {
+        let options = DebugOptions { breakpoints: vec![], step_limit: Some(100) };
+        let mut debugger = Debugger::new(&program(1, vec![1, 7, 3, 0]), options);
+
+        assert_eq!(Ok(Stop::StepLimit), debugger.run());
+        assert_eq!(100, debugger.trace().len());
+        assert!(!debugger.is_halted());
+    }
+
+    #[test]
+    fn it_exports_the_trace_as_json_lines() {
+        let mut debugger = Debugger::new(&program(729, vec![0, 1, 5, 4, 3, 0]), DebugOptions::default());
+        debugger.run().unwrap();
+
+        let json_lines = to_json_lines(debugger.trace());
+        assert_eq!(
+            r#"{"step":1,"instruction_pointer":0,"opcode":"adv","operand":1,"registers_before":[729,0,0],"registers_after":[364,0,0],"output":null}"#,
+            json_lines.lines().next().unwrap()
+        );
+        assert_eq!(30, json_lines.lines().count());
+        assert_eq!(Ok(debugger.trace().to_vec()), from_json_lines(&json_lines));
+        assert!(from_json_lines("{\"step\":1}").is_err());
+    }
+}
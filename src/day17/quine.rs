@@ -0,0 +1,241 @@
+use super::{Machine, Opcode, Program, STEP_LIMIT};
+use crate::parse_error::ParseError;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuineError {
+    Parse(ParseError),
+    IncompleteInstruction,
+    InvalidOpcode { address: usize },
+    MissingFinalJump,
+    UnexpectedJump { address: usize },
+    ReservedOperand { address: usize },
+    OutputsPerLoop(usize),
+    ShiftsPerLoop(usize),
+    UnsupportedShift { address: usize },
+    ReadsPreviousIteration { address: usize, register: char },
+    NoSolution,
+}
+
+impl fmt::Display for QuineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuineError::Parse(error) => write!(f, "{}", error),
+            QuineError::IncompleteInstruction => write!(f, "the last instruction has no operand"),
+            QuineError::InvalidOpcode { address } => write!(f, "invalid opcode at {}", address),
+            QuineError::MissingFinalJump => write!(f, "the program does not end with jnz 0"),
+            QuineError::UnexpectedJump { address } => write!(f, "jump at {} before the end of the loop", address),
+            QuineError::ReservedOperand { address } => write!(f, "reserved combo operand 7 at {}", address),
+            QuineError::OutputsPerLoop(count) => write!(f, "the loop prints {} values instead of 1", count),
+            QuineError::ShiftsPerLoop(count) => write!(f, "the loop shifts A {} times instead of 1", count),
+            QuineError::UnsupportedShift { address } => write!(f, "A is not shifted by 1 to 3 bits at {}", address),
+            QuineError::ReadsPreviousIteration { address, register } => {
+                write!(f, "register {} is read at {} before being written in the loop", register, address)
+            }
+            QuineError::NoSolution => write!(f, "no value of A prints the target"),
+        }
+    }
+}
+
+impl Error for QuineError {}
+
+impl From<ParseError> for QuineError {
+    fn from(error: ParseError) -> Self {
+        QuineError::Parse(error)
+    }
+}
+
+/// Checks that the program is a single loop printing one value and shifting A by a constant number
+/// of bits, with B and C computed from A in every iteration. Returns the number of bits.
+pub fn check_structure(instructions: &[usize]) -> Result<u32, QuineError> {
+    if !instructions.len().is_multiple_of(2) {
+        return Err(QuineError::IncompleteInstruction);
+    }
+    if !instructions.ends_with(&[Opcode::Jnz.code(), 0]) {
+        return Err(QuineError::MissingFinalJump);
+    }
+
+    let mut shifts = vec![];
+    let mut outputs = 0;
+    let mut written = [false; 3];
+    for (address, pair) in instructions[..instructions.len() - 2].chunks(2).enumerate() {
+        let address = address * 2;
+        let opcode = Opcode::from_code(pair[0]).ok_or(QuineError::InvalidOpcode { address })?;
+        let operand = pair[1];
+
+        let mut reads = vec![];
+        if opcode.takes_combo_operand() {
+            match operand {
+                4..=6 => reads.push(operand - 4),
+                7 => return Err(QuineError::ReservedOperand { address }),
+                _ => {}
+            }
+        }
+
+        let writes = match opcode {
+            Opcode::Adv => {
+                shifts.push((address, operand));
+                Some(0)
+            }
+            Opcode::Bxl => {
+                reads.push(1);
+                Some(1)
+            }
+            Opcode::Bxc => {
+                reads.extend([1, 2]);
+                Some(1)
+            }
+            Opcode::Bst | Opcode::Bdv => Some(1),
+            Opcode::Cdv => Some(2),
+            Opcode::Out => {
+                outputs += 1;
+                None
+            }
+            Opcode::Jnz => return Err(QuineError::UnexpectedJump { address }),
+        };
+
+        if let Some(&register) = reads.iter().find(|&&r| r != 0 && !written[r]) {
+            return Err(QuineError::ReadsPreviousIteration { address, register: ['A', 'B', 'C'][register] });
+        }
+        if let Some(register) = writes {
+            written[register] = true;
+        }
+    }
+
+    if outputs != 1 {
+        return Err(QuineError::OutputsPerLoop(outputs));
+    }
+    match shifts.as_slice() {
+        [(_, operand @ 1..=3)] => Ok(*operand as u32),
+        [(address, _)] => Err(QuineError::UnsupportedShift { address: *address }),
+        _ => Err(QuineError::ShiftsPerLoop(shifts.len())),
+    }
+}
+
+/// The smallest value of register A making the program print the target
+pub fn smallest_register_a(program: &Program, target: &[usize]) -> Result<u64, QuineError> {
+    let shift = check_structure(&program.instructions)?;
+
+    let mut solutions = vec![];
+    search(program, target, shift, target.len(), 0, true, &mut solutions);
+    solutions.first().copied().ok_or(QuineError::NoSolution)
+}
+
+/// Sorted from the smallest value
+pub fn all_register_a_values(program: &Program, target: &[usize]) -> Result<Vec<u64>, QuineError> {
+    let shift = check_structure(&program.instructions)?;
+
+    let mut solutions = vec![];
+    search(program, target, shift, target.len(), 0, false, &mut solutions);
+    Ok(solutions)
+}
+
+/// A has one digit of `shift` bits per printed value. Digits are picked from the most significant
+/// one and in increasing order, so solutions are found sorted.
+fn search(
+    program: &Program,
+    target: &[usize],
+    shift: u32,
+    index: usize,
+    register_a: u64,
+    only_first: bool,
+    solutions: &mut Vec<u64>,
+) {
+    if index == 0 {
+        if register_a != 0 && prints(program, register_a).is_some_and(|output| output == target) {
+            solutions.push(register_a);
+        }
+        return;
+    }
+
+    let Some(prefix) = register_a.checked_mul(1 << shift) else {
+        return;
+    };
+    for digit in 0..1 << shift {
+        let candidate = prefix + digit;
+        if candidate == 0 || first_output(program, candidate) != Some(target[index - 1]) {
+            continue;
+        }
+
+        search(program, target, shift, index - 1, candidate, only_first, solutions);
+        if only_first && !solutions.is_empty() {
+            return;
+        }
+    }
+}
+
+/// There is no jump before the end of the loop, so the first output comes within a single pass.
+/// A value of A making the machine fail cannot print the target.
+fn first_output(program: &Program, register_a: u64) -> Option<usize> {
+    let mut machine = Machine::new(register_a, program.register_b, program.register_c);
+    while machine.stdout.is_empty() && !machine.is_halted(&program.instructions) {
+        machine.step(&program.instructions).ok()?;
+    }
+    machine.stdout.first().copied()
+}
+
+fn prints(program: &Program, register_a: u64) -> Option<Vec<usize>> {
+    let mut machine = Machine::new(register_a, program.register_b, program.register_c);
+    machine.execute_instructions(&program.instructions, STEP_LIMIT).ok()?;
+    Some(machine.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::assembly::assemble;
+    use indoc::indoc;
+
+    fn program(listing: &str) -> Program {
+        Program { register_a: 0, register_b: 0, register_c: 0, instructions: assemble(listing).unwrap() }
+    }
+
+    #[test]
+    fn it_checks_the_structure_of_the_program() {
+        assert_eq!(Ok(3), check_structure(&[0, 3, 5, 4, 3, 0]));
+        assert_eq!(Ok(3), check_structure(&[2, 4, 1, 3, 7, 5, 4, 1, 0, 3, 1, 5, 5, 5, 3, 0]));
+
+        assert_eq!(Err(QuineError::IncompleteInstruction), check_structure(&[0, 3, 5, 4, 3]));
+        assert_eq!(Err(QuineError::MissingFinalJump), check_structure(&program("adv 3\nout A\njnz 2").instructions));
+        assert_eq!(Err(QuineError::UnexpectedJump { address: 2 }), check_structure(&program("adv 3\njnz 0\nout A\njnz 0").instructions));
+        assert_eq!(Err(QuineError::ReservedOperand { address: 2 }), check_structure(&program("adv 3\nout 7\njnz 0").instructions));
+        assert_eq!(Err(QuineError::OutputsPerLoop(2)), check_structure(&program("adv 3\nout A\nout A\njnz 0").instructions));
+        assert_eq!(Err(QuineError::ShiftsPerLoop(0)), check_structure(&program("bdv 3\nout A\njnz 0").instructions));
+        assert_eq!(Err(QuineError::UnsupportedShift { address: 2 }), check_structure(&program("bst A\nadv B\nout B\njnz 0").instructions));
+        assert_eq!(Err(QuineError::InvalidOpcode { address: 0 }), check_structure(&[8, 3, 5, 4, 3, 0]));
+        assert_eq!(
+            Err(QuineError::ReadsPreviousIteration { address: 2, register: 'C' }),
+            check_structure(&program("bst A\nbxc 0\nadv 3\nout B\njnz 0").instructions)
+        );
+    }
+
+    #[test]
+    fn it_finds_the_smallest_register_a_printing_the_target() {
+        let quine = program("adv 3\nout A\njnz 0");
+
+        assert_eq!(Ok(117440), smallest_register_a(&quine, &quine.instructions));
+        assert_eq!(Ok(8 * (5 * 8 + 1)), smallest_register_a(&quine, &[1, 5, 0]));
+        assert_eq!(Err(QuineError::NoSolution), smallest_register_a(&quine, &[1, 5, 1]));
+        assert_eq!(Err(QuineError::NoSolution), smallest_register_a(&quine, &[]));
+    }
+
+    #[test]
+    fn it_lists_every_solution() {
+        let program = program(indoc! {"
+            bst A
+            bxl 2
+            cdv B
+            bxc 0
+            adv 2
+            out B
+            jnz 0"});
+
+        assert_eq!(Ok(vec![36, 38, 41]), all_register_a_values(&program, &[6, 2, 2]));
+        assert_eq!(Ok(36), smallest_register_a(&program, &[6, 2, 2]));
+        assert_eq!(Ok(vec![]), all_register_a_values(&program, &[1, 2, 3]));
+
+        let brute_force: Vec<u64> = (1..1 << 6).filter(|&a| prints(&program, a) == Some(vec![4, 4, 0])).collect();
+        assert_eq!(Ok(brute_force), all_register_a_values(&program, &[4, 4, 0]));
+    }
+}
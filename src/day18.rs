@@ -1,8 +1,309 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse_error::{lines, Line, ParseError};
+use crate::search::bfs;
+use crate::solver::{Answer, Solver};
+
+pub struct Day18;
+
+const UNREACHABLE_END: &str = "the end cannot be reached from the start";
+const NO_BLOCKING_BYTE: &str = "no byte cuts the end off from the start";
+
+impl Solver for Day18 {
+    type Input = Vec<Byte>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_bytes(input)
+    }
+
+    fn part1(&self, bytes: &Self::Input) -> Answer {
+        minimum_steps(bytes, 1024, 70).ok_or(UNREACHABLE_END).into()
+    }
+
+    fn part2(&self, bytes: &Self::Input) -> Answer {
+        blocking_byte(bytes, 70, Point::ORIGIN, Point::new(70, 70))
+            .map(|byte| byte.to_string())
+            .ok_or(NO_BLOCKING_BYTE)
+            .into()
+    }
+}
+
+pub type Byte = Point;
+
+pub fn parse_bytes(input: &str) -> Result<Vec<Byte>, ParseError> {
+    lines(18, input)
+        .map(|line| parse_byte(&line))
+        .collect()
+}
+
+fn parse_byte(line: &Line) -> Result<Byte, ParseError> {
+    let (x, y) = line.split_once(",")?;
+    Ok(Point::new(line.parse::<isize>(x, "a coordinate")?, line.parse::<isize>(y, "a coordinate")?))
+}
+
+pub fn first_coordinate_preventing_reaching_end(input: &str, range: usize) -> Result<Option<String>, ParseError> {
+    let end = Point::new(range as isize, range as isize);
+    Ok(blocking_byte(&parse_bytes(input)?, range, Point::ORIGIN, end).map(|byte| byte.to_string()))
+}
+
+/// Removes the fallen bytes from the last one until the start and the end are connected again, the
+/// byte removed last is the first one cutting them apart. None when they never get cut apart.
+pub fn blocking_byte(bytes: &[Byte], range: usize, start: Point, end: Point) -> Option<Byte> {
+    let mut space = MemorySpace::new(range);
+    for &byte in bytes {
+        space.fall(byte);
+    }
+
+    let mut regions = space.into_regions();
+    while !regions.are_connected(start, end) {
+        let byte = regions.remove_last()?;
+        if regions.are_connected(start, end) {
+            return Some(byte);
+        }
+    }
+    None
+}
+
+/// A square memory space from (0, 0) to (range, range) where bytes fall. Its free cells are only
+/// grouped into regions once the bytes have fallen, see `into_regions`.
+#[derive(Debug, Clone)]
+pub struct MemorySpace {
+    /// How many fallen bytes cover every cell, a byte can fall twice on the same cell
+    corrupted: Grid<usize>,
+    fallen: Vec<Byte>,
+}
+
+impl MemorySpace {
+    pub fn new(range: usize) -> Self {
+        let size = range + 1;
+        Self { corrupted: Grid::new(size, size, 0), fallen: vec![] }
+    }
+
+    pub fn fallen(&self) -> &[Byte] {
+        &self.fallen
+    }
+
+    pub fn is_corrupted(&self, position: Point) -> bool {
+        self.corrupted.get(position).is_some_and(|&count| count > 0)
+    }
+
+    /// Bytes outside of the memory space are ignored
+    pub fn fall(&mut self, byte: Byte) -> bool {
+        let Some(count) = self.corrupted.get_mut(byte) else {
+            return false;
+        };
+
+        *count += 1;
+        self.fallen.push(byte);
+        true
+    }
+
+    /// Groups the free cells into regions once, from then on bytes can only be removed
+    pub fn into_regions(self) -> Regions {
+        let mut regions = Regions {
+            regions: UnionFind::new(self.corrupted.width() * self.corrupted.height()),
+            space: self,
+        };
+        let free: Vec<_> = regions.space.corrupted.positions().filter(|&p| !regions.space.is_corrupted(p)).collect();
+        for position in free {
+            regions.join_neighbours(position);
+        }
+        regions
+    }
+}
+
+/// The free cells of a memory space grouped into connected regions. Bytes are removed from the last
+/// fallen one, which merges the regions around it, so every operation is almost constant.
+#[derive(Debug, Clone)]
+pub struct Regions {
+    space: MemorySpace,
+    regions: UnionFind,
+}
+
+impl Regions {
+    pub fn space(&self) -> &MemorySpace {
+        &self.space
+    }
+
+    pub fn remove_last(&mut self) -> Option<Byte> {
+        let byte = self.space.fallen.pop()?;
+        self.space.corrupted[byte] -= 1;
+        if !self.space.is_corrupted(byte) {
+            self.join_neighbours(byte);
+        }
+        Some(byte)
+    }
+
+    pub fn are_connected(&mut self, a: Point, b: Point) -> bool {
+        let space = &self.space;
+        if !space.corrupted.contains(a) || !space.corrupted.contains(b) || space.is_corrupted(a) || space.is_corrupted(b) {
+            return false;
+        }
+
+        let (a, b) = (self.index(a), self.index(b));
+        self.regions.find(a) == self.regions.find(b)
+    }
+
+    fn join_neighbours(&mut self, position: Point) {
+        let free: Vec<_> = self.space.corrupted.neighbours(position).filter(|&n| !self.space.is_corrupted(n)).collect();
+        for neighbour in free {
+            let (a, b) = (self.index(position), self.index(neighbour));
+            self.regions.union(a, b);
+        }
+    }
+
+    fn index(&self, position: Point) -> usize {
+        position.y as usize * self.space.corrupted.width() + position.x as usize
+    }
+}
+
+/// Disjoint sets with path halving and union by size, so that both operations are almost constant
+#[derive(Debug, Clone)]
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self { parents: (0..size).collect(), sizes: vec![1; size] }
+    }
+
+    fn find(&mut self, mut element: usize) -> usize {
+        while self.parents[element] != element {
+            self.parents[element] = self.parents[self.parents[element]];
+            element = self.parents[element];
+        }
+        element
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            (a, b) = (b, a);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+    }
+}
+
+pub fn find_minimum_steps_to_take(input: &str, steps_to_take: usize, range: usize) -> Result<Option<usize>, ParseError> {
+    Ok(minimum_steps(&parse_bytes(input)?, steps_to_take, range))
+}
+
+/// The bytes have all fallen before the walk starts and nothing changes while walking
+fn minimum_steps(bytes: &[Byte], steps_to_take: usize, range: usize) -> Option<usize> {
+    let end = Point::new(range as isize, range as isize);
+    let options = WalkOptions { range, end, already_fallen: steps_to_take, bytes_per_step: 0, ..WalkOptions::default() };
+    walk(bytes, &options).map(|path| path.len() - 1)
+}
+
+/// The first `already_fallen` bytes are down when the walk starts, then `bytes_per_step` more fall
+/// before every step. A step must not end on a fallen byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkOptions {
+    pub range: usize,
+    pub start: Point,
+    pub end: Point,
+    pub already_fallen: usize,
+    pub bytes_per_step: usize,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            range: 70,
+            start: Point::ORIGIN,
+            end: Point::new(70, 70),
+            already_fallen: 1024,
+            bytes_per_step: 1,
+        }
+    }
+}
+
+/// When every cell of the memory space gets corrupted, so that the space at any time is known
+struct FallTimes {
+    fallen_at: Grid<Option<usize>>,
+    already_fallen: usize,
+    bytes_per_step: usize,
+    /// The time from which no byte falls anymore
+    settled: usize,
+}
+
+impl FallTimes {
+    fn new(bytes: &[Byte], options: &WalkOptions) -> Self {
+        let size = options.range + 1;
+        let mut fallen_at = Grid::new(size, size, None);
+        for (index, &byte) in bytes.iter().enumerate() {
+            if let Some(cell @ None) = fallen_at.get_mut(byte) {
+                *cell = Some(index);
+            }
+        }
+
+        let settled = match options.bytes_per_step {
+            0 => 0,
+            per_step => bytes.len().saturating_sub(options.already_fallen).div_ceil(per_step),
+        };
+        Self { fallen_at, already_fallen: options.already_fallen, bytes_per_step: options.bytes_per_step, settled }
+    }
+
+    fn is_free(&self, position: Point, time: usize) -> bool {
+        let fallen = self.already_fallen + time * self.bytes_per_step;
+        self.fallen_at.get(position).is_some_and(|index| index.is_none_or(|index| index >= fallen))
+    }
+}
+
+/// A breadth-first search over (position, time) that stops once the bytes have settled and the end
+/// cannot be reached. Returns the position at every time, from the start to the end.
+pub fn walk(bytes: &[Byte], options: &WalkOptions) -> Option<Vec<Point>> {
+    let times = FallTimes::new(bytes, options);
+    if !times.is_free(options.start, 0) {
+        return None;
+    }
+
+    let search = bfs(
+        [(options.start, 0)],
+        |&(position, time)| {
+            let next = (time + 1).min(times.settled);
+            position.neighbours().into_iter()
+                .filter(|&n| times.is_free(n, time + 1))
+                .map(|n| (n, next))
+                .collect::<Vec<_>>()
+        },
+        |&(position, _)| position == options.end,
+    );
+    search.goal_path().map(|path| path.into_iter().map(|(position, _)| position).collect())
+}
+
+/// The memory space at every step of the path: visited cells 'O', fallen bytes '#' even on visited
+/// cells and the walker '@'
+pub fn render_frames(bytes: &[Byte], options: &WalkOptions, path: &[Point]) -> Vec<String> {
+    let times = FallTimes::new(bytes, options);
+    (0..path.len())
+        .map(|time| {
+            let mut frame = times.fallen_at.map(|_| '.');
+            for &position in &path[..time] {
+                frame.set(position, 'O');
+            }
+            for position in frame.positions().collect::<Vec<_>>() {
+                if !times.is_free(position, time) {
+                    frame.set(position, '#');
+                }
+            }
+            frame.set(path[time], '@');
+            frame.to_string()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use std::collections::{HashSet, VecDeque};
 
     #[test]
     fn it_finds_minimum_steps_to_take() {
@@ -34,7 +335,7 @@ mod tests {
         2,0
         "};
 
-        assert_eq!(Some(22), find_minimum_steps_to_take(input, 12, 6));
+        assert_eq!(Ok(Some(22)), find_minimum_steps_to_take(input, 12, 6));
     }
 
     #[test]
@@ -67,89 +368,78 @@ mod tests {
         2,0
         "};
 
-        assert_eq!("6,1", first_coordinate_preventing_reaching_end(input, 12, 6))
+        assert_eq!(Ok(Some("6,1".to_string())), first_coordinate_preventing_reaching_end(input, 6))
     }
 
     #[test]
-    fn it_solves_both_puzzles() {
-        let input = &read_input_file("input_18");
+    fn it_finds_the_blocking_byte_between_any_cells() {
+        let wall = [Point::new(1, 0), Point::new(1, 1), Point::new(3, 3), Point::new(1, 2)];
 
-        assert_eq!(Some(312), find_minimum_steps_to_take(input, 1024, 70));
-        assert_eq!("28,26", first_coordinate_preventing_reaching_end(input, 1024, 70))
+        assert_eq!(Some(Point::new(1, 2)), blocking_byte(&wall, 2, Point::ORIGIN, Point::new(2, 2)));
+        assert_eq!(Some(Point::new(1, 2)), blocking_byte(&wall, 2, Point::new(2, 0), Point::new(0, 1)));
+        assert_eq!(None, blocking_byte(&wall, 2, Point::ORIGIN, Point::new(0, 2)));
+        assert_eq!(Some(Point::new(2, 2)), blocking_byte(&[Point::new(2, 2)], 2, Point::ORIGIN, Point::new(2, 2)));
     }
 
-    fn first_coordinate_preventing_reaching_end(input: &str, start: usize, range: isize) -> String {
-        let corrupted_bytes = read_lines(input);
-        let mut start = start;
-        let mut end = corrupted_bytes.len();
-
-        while (start + end) / 2 != start {
-            if find_minimum_steps_to_take(input, (start + end) / 2, range).is_some() {
-                start = (start + end) / 2;
-            } else {
-                end = (start + end) / 2;
-            }
-        }
-
-        corrupted_bytes[end - 1].clone()
-    }
+    #[test]
+    fn it_keeps_track_of_regions_as_bytes_are_removed() {
+        let (start, end) = (Point::ORIGIN, Point::new(2, 2));
+        assert!(MemorySpace::new(2).into_regions().are_connected(start, end));
 
-    fn find_minimum_steps_to_take(input: &str, steps_to_take: usize, range: isize) -> Option<usize> {
-        let mut corrupted_bytes = HashSet::new();
-        for line in read_lines(input).iter().take(steps_to_take) {
-            let raw_digits = line.split(",").collect::<Vec<_>>();
-            let x = raw_digits[0].parse::<isize>().unwrap();
-            let y = raw_digits[1].parse::<isize>().unwrap();
-            corrupted_bytes.insert((x, y));
+        let mut space = MemorySpace::new(2);
+        assert!(!space.fall(Point::new(3, 0)));
+        for byte in [Point::new(0, 1), Point::new(1, 0), Point::new(1, 1), Point::new(1, 2), Point::new(1, 2)] {
+            assert!(space.fall(byte));
         }
+        let mut regions = space.into_regions();
+        assert!(!regions.are_connected(start, end));
+        assert!(regions.are_connected(Point::new(2, 0), end));
+        assert!(!regions.are_connected(start, Point::new(3, 0)));
 
-        let mut queue = VecDeque::new();
-        queue.push_back(((0, 0), 0));
-
-        let mut visited = HashSet::new();
-
-        while let Some((position, steps)) = queue.pop_front() {
-            if position == (range, range) {
-                return Some(steps);
-            }
-
-            if visited.contains(&position) {
-                continue;
-            }
-
-            visited.insert(position.clone());
-
-            for n in neighbours(position, range) {
-                if corrupted_bytes.contains(&n) {
-                    continue;
-                }
-
-                queue.push_back((n, steps + 1))
-            }
-        }
+        assert_eq!(Some(Point::new(1, 2)), regions.remove_last());
+        assert!(!regions.are_connected(Point::new(0, 2), end));
+        assert_eq!(Some(Point::new(1, 2)), regions.remove_last());
+        assert!(regions.are_connected(Point::new(0, 2), end));
+        assert!(!regions.are_connected(start, end));
+        assert!(!regions.are_connected(end, Point::new(1, 1)));
 
-        None
+        assert_eq!(Some(Point::new(1, 1)), regions.remove_last());
+        assert_eq!(Some(Point::new(1, 0)), regions.remove_last());
+        assert!(regions.are_connected(start, end));
+        assert_eq!(1, regions.space().fallen().len());
     }
 
-    fn neighbours((x, y): (isize, isize), range: isize) -> Vec<(isize, isize)> {
-        let mut result = vec![];
+    #[test]
+    fn it_walks_while_bytes_fall() {
+        let bytes = [Point::new(1, 1), Point::new(2, 1), Point::new(0, 2), Point::new(1, 2)];
+        let options = WalkOptions { range: 2, end: Point::new(2, 2), already_fallen: 0, ..WalkOptions::default() };
 
-        if x + 1 <= range {
-            result.push((x + 1, y))
-        }
+        let path = walk(&bytes, &options).unwrap();
+        assert_eq!(vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)], path[..3]);
+        assert_eq!(None, walk(&bytes, &WalkOptions { already_fallen: 4, bytes_per_step: 0, ..options.clone() }));
+        assert_eq!(None, walk(&bytes, &WalkOptions { start: Point::new(1, 1), already_fallen: 1, ..options.clone() }));
+        assert_eq!(Some(3), walk(&bytes, &WalkOptions { start: Point::new(2, 0), ..options.clone() }).map(|p| p.len()));
+        assert_eq!(
+            vec!["@..\n...\n...", "O..\n@#.\n...", "O..\nO##\n@..", "O..\nO##\n#@.", "O..\nO##\n##@"],
+            render_frames(&bytes, &options, &path)
+        );
+    }
 
-        if x - 1 >= 0 {
-            result.push((x - 1, y))
-        }
+    #[test]
+    fn it_reports_invalid_bytes() {
+        assert_eq!(Err(ParseError::new(18, 2, 4, "','")), parse_bytes("5,4\n4 2"));
+        assert_eq!(Err(ParseError::new(18, 3, 3, "a coordinate")), parse_bytes("5,4\n4,2\n4,y"));
 
-        if y + 1 <= range {
-            result.push((x, y + 1))
-        }
+        let walled_in = vec![Point::new(0, 1), Point::new(1, 0)];
+        assert_eq!(Answer::Error(UNREACHABLE_END.to_string()), Day18.part1(&walled_in));
+        assert_eq!(Answer::Error(NO_BLOCKING_BYTE.to_string()), Day18.part2(&vec![]));
+    }
 
-        if y - 1 >= 0 {
-            result.push((x, y - 1))
-        }
+    #[test]
+    fn it_solves_both_puzzles() {
+        let input = &personal_input!("input_18");
 
-        result
+        assert_eq!(Ok(Some(312)), find_minimum_steps_to_take(input, 1024, 70));
+        assert_eq!(Ok(Some("28,26".to_string())), first_coordinate_preventing_reaching_end(input, 70))
     }
-}
\ No newline at end of file
+}
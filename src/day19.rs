@@ -1,8 +1,106 @@
+use crate::parse_error::{empty_input, lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use std::collections::HashMap;
+
+pub struct Day19;
+
+impl Solver for Day19 {
+    type Input = Towels;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_towels(input)
+    }
+
+    fn part1(&self, towels: &Self::Input) -> Answer {
+        count_designs(towels).0.into()
+    }
+
+    fn part2(&self, towels: &Self::Input) -> Answer {
+        count_designs(towels).1.into()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Towels {
+    pub patterns: Vec<String>,
+    pub designs: Vec<String>,
+}
+
+pub fn parse_towels(input: &str) -> Result<Towels, ParseError> {
+    let mut lines = lines(19, input);
+
+    let line = lines.next().ok_or(empty_input(19, "towel patterns"))?;
+    let patterns = line.text
+        .split(", ")
+        .map(|pattern| parse_stripes(&line, pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match lines.next() {
+        Some(separator) if separator.text.is_empty() => {}
+        Some(separator) => return Err(separator.error_at(separator.text, "an empty line")),
+        None => return Err(ParseError::new(19, 2, 1, "an empty line")),
+    }
+
+    let designs = lines
+        .map(|line| parse_stripes(&line, line.text))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Towels { patterns, designs })
+}
+
+fn parse_stripes(line: &Line, stripes: &str) -> Result<String, ParseError> {
+    match stripes.char_indices().find(|(_, c)| !"wubrg".contains(*c)) {
+        Some((index, _)) => Err(line.error_at(&stripes[index..], "a stripe colour 'w', 'u', 'b', 'r' or 'g'")),
+        None if stripes.is_empty() => Err(line.error_at(stripes, "a stripe colour 'w', 'u', 'b', 'r' or 'g'")),
+        None => Ok(stripes.to_string()),
+    }
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize), ParseError> {
+    Ok(count_designs(&parse_towels(input)?))
+}
+
+fn count_designs(towels: &Towels) -> (usize, usize) {
+    let mut memo: HashMap<String, usize> = HashMap::new();
+    let mut possible_designs = 0;
+    let mut all_different_ways = 0;
+    for design in &towels.designs {
+        let design_combinations = possible_combinations_per_pattern(design.clone(), &towels.patterns, &mut memo);
+        if design_combinations > 0 {
+            possible_designs += 1;
+        }
+        all_different_ways += design_combinations;
+    }
+    (possible_designs, all_different_ways)
+}
+
+fn possible_combinations_per_pattern(design: String, patterns: &[String], memo: &mut HashMap<String, usize>) -> usize {
+    if design.is_empty() {
+        return 1;
+    }
+
+    if let Some(&result) = memo.get(&design) {
+        return result;
+    }
+
+    let mut possible_combinations = 0;
+    for i in 0..design.len() {
+        let part = design[0..i + 1].to_string();
+        if !patterns.contains(&part) {
+            continue;
+        }
+
+        possible_combinations += possible_combinations_per_pattern(design[i + 1..design.len()].to_string(), patterns, memo);
+    }
+    memo.insert(design.clone(), possible_combinations);
+    possible_combinations
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use std::collections::HashMap;
 
     #[test]
     fn it_solve_for_test_input() {
@@ -19,57 +117,21 @@ mod tests {
         bbrgwb
         "};
 
-        assert_eq!((6, 16), solve(input));
+        assert_eq!(Ok((6, 16)), solve(input));
     }
 
     #[test]
-    fn it_solves_both_puzzle() {
-        let input = &read_input_file("input_19");
-
-        assert_eq!((242, 595975512785325), solve(input));
+    fn it_reports_invalid_towels() {
+        let stripe = "a stripe colour 'w', 'u', 'b', 'r' or 'g'";
+        assert_eq!(Err(ParseError::new(19, 1, 5, stripe)), parse_towels("r, wx, b\n\nbrwrr"));
+        assert_eq!(Err(ParseError::new(19, 3, 3, stripe)), parse_towels("r, wr, b\n\nbrZrr"));
+        assert_eq!(Err(ParseError::new(19, 2, 1, "an empty line")), parse_towels("r, wr, b\nbrwrr"));
     }
 
-    fn solve(input: &str) -> (usize, usize) {
-        let lines = read_lines(input);
-
-        let patterns = lines[0].clone()
-            .split(", ")
-            .map(|s| s.to_string())
-            .collect::<Vec<_>>();
-
-        let mut memo: HashMap<String, usize> = HashMap::new();
-        let mut possible_designs = 0;
-        let mut all_different_ways = 0;
-        for index in 2..lines.len() {
-            let design = lines[index].clone();
-            let design_combinations = possible_combinations_per_pattern(design.clone(), &patterns, &mut memo);
-            if design_combinations > 0 {
-                possible_designs += 1;
-            }
-            all_different_ways += design_combinations;
-        }
-        (possible_designs, all_different_ways)
-    }
-
-    fn possible_combinations_per_pattern(design: String, patterns: &Vec<String>, memo: &mut HashMap<String, usize>) -> usize {
-        if design.is_empty() {
-            return 1;
-        }
-
-        if let Some(&result) = memo.get(&design) {
-            return result;
-        }
-
-        let mut possible_combinations = 0;
-        for i in 0..design.len() {
-            let part = design[0..i + 1].to_string();
-            if !patterns.contains(&part) {
-                continue;
-            }
+    #[test]
+    fn it_solves_both_puzzle() {
+        let input = &personal_input!("input_19");
 
-            possible_combinations += possible_combinations_per_pattern(design[i + 1..design.len()].to_string(), patterns, memo);
-        }
-        memo.insert(design.clone(), possible_combinations);
-        possible_combinations
+        assert_eq!(Ok((242, 595975512785325)), solve(input));
     }
-}
\ No newline at end of file
+}
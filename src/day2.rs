@@ -1,11 +1,76 @@
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+
+pub struct Day2;
+
+impl Solver for Day2 {
+    type Input = Vec<Vec<usize>>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(&self, reports: &Self::Input) -> Answer {
+        count_safe(reports, is_report_safe).into()
+    }
+
+    fn part2(&self, reports: &Self::Input) -> Answer {
+        count_safe(reports, is_report_safe_with_fallback).into()
+    }
+}
+
+pub fn is_report_safe_with_fallback(report: &[usize]) -> bool {
+    if is_report_safe(report) {
+        return true;
+    }
+
+    (0..report.len()).any(|index| {
+        let mut r = report.to_vec();
+        r.remove(index);
+        is_report_safe(&r)
+    })
+}
+
+pub fn count_safe_report(input: &str, report_checker: fn(&[usize]) -> bool) -> Result<usize, ParseError> {
+    Ok(count_safe(&parse_input(input)?, report_checker))
+}
+
+fn count_safe(reports: &[Vec<usize>], report_checker: fn(&[usize]) -> bool) -> usize {
+    reports.iter()
+        .filter(|&r| report_checker(r))
+        .count()
+}
+
+pub fn is_report_safe(line: &[usize]) -> bool {
+    let diffs: Vec<_> = line.windows(2)
+        .map(|pair| pair[1] as isize - pair[0] as isize)
+        .collect();
+    (diffs.iter().all(|d| d.is_positive()) || diffs.iter().all(|d| d.is_negative()))
+        && diffs.iter().all(|d| d.abs() > 0 && d.abs() < 4)
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Vec<usize>>, ParseError> {
+    lines(2, input)
+        .map(|line| parse_line(&line))
+        .collect()
+}
+
+
+pub fn parse_line(line: &Line) -> Result<Vec<usize>, ParseError> {
+    line.text.split(" ")
+        .map(|n| line.parse::<usize>(n, "a level"))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use crate::input_reader::{read_input_file, read_lines};
 
     #[test]
     fn it_parse_the_line() {
-        assert_eq!(vec![7, 6, 4, 2, 1], parse_line("7 6 4 2 1"))
+        assert_eq!(Ok(vec![7, 6, 4, 2, 1]), parse_line(&Line::new(2, 0, "7 6 4 2 1")))
     }
 
     #[test]
@@ -18,18 +83,23 @@ mod tests {
         8 6 4 4 1
         1 3 6 7 9"};
         assert_eq!(
-            vec![
+            Ok(vec![
                 vec![7, 6, 4, 2, 1],
                 vec![1, 2, 7, 8, 9],
                 vec![9, 7, 6, 2, 1],
                 vec![1, 3, 2, 4, 5],
                 vec![8, 6, 4, 4, 1],
                 vec![1, 3, 6, 7, 9],
-            ],
-            parse_input(&input)
+            ]),
+            parse_input(input)
         )
     }
 
+    #[test]
+    fn it_reports_invalid_levels() {
+        assert_eq!(Err(ParseError::new(2, 2, 3, "a level")), parse_input("7 6 4\n1 2x 7"));
+    }
+
     #[test]
     fn it_counts_safe_report() {
         let input = indoc! {"
@@ -39,71 +109,32 @@ mod tests {
         1 3 2 4 5
         8 6 4 4 1
         1 3 6 7 9"};
-        assert_eq!(2, count_safe_report(&input, is_report_safe))
+        assert_eq!(Ok(2), count_safe_report(input, is_report_safe))
     }
 
     #[test]
     fn it_solves_the_first_puzzle() {
-        let input = read_input_file("input_02");
-        assert_eq!(624, count_safe_report(&input, is_report_safe))
+        let input = personal_input!("input_02");
+        assert_eq!(Ok(624), count_safe_report(&input, is_report_safe))
     }
 
     #[test]
     fn it_checks_that_report_is_safe() {
-        assert_eq!(true, is_report_safe(&vec![7, 6, 4, 2, 1]));
-        assert_eq!(false, is_report_safe(&vec![1, 2, 7, 8, 9]));
-        assert_eq!(false, is_report_safe(&vec![1, 3, 2, 4, 5]))
+        assert!(is_report_safe(&[7, 6, 4, 2, 1]));
+        assert!(!is_report_safe(&[1, 2, 7, 8, 9]));
+        assert!(!is_report_safe(&[1, 3, 2, 4, 5]))
     }
 
     #[test]
     fn it_checks_that_report_is_safe_with_fallback() {
-        assert_eq!(true, is_report_safe_with_fallback(&vec![7, 6, 4, 2, 1]));
-        assert_eq!(false, is_report_safe_with_fallback(&vec![1, 2, 7, 8, 9]));
-        assert_eq!(true, is_report_safe_with_fallback(&vec![1, 3, 2, 4, 5]))
+        assert!(is_report_safe_with_fallback(&[7, 6, 4, 2, 1]));
+        assert!(!is_report_safe_with_fallback(&[1, 2, 7, 8, 9]));
+        assert!(is_report_safe_with_fallback(&[1, 3, 2, 4, 5]))
     }
 
     #[test]
     fn it_solves_the_second_puzzle() {
-        let input = read_input_file("input_02");
-        assert_eq!(658, count_safe_report(&input, is_report_safe_with_fallback))
-    }
-
-    fn is_report_safe_with_fallback(report: &Vec<usize>) -> bool {
-        if is_report_safe(report) {
-            return true;
-        }
-
-        (0..report.len()).any(|index| {
-            let mut r = report.clone();
-            r.remove(index);
-            is_report_safe(&r)
-        })
-    }
-
-    fn count_safe_report(input: &str, report_checker: fn(&Vec<usize>) -> bool) -> usize {
-        parse_input(input).iter()
-            .filter(|&r| report_checker(r))
-            .count()
-    }
-
-    fn is_report_safe(line: &Vec<usize>) -> bool {
-        let diffs: Vec<_> = line.windows(2)
-            .map(|pair| pair[1] as isize - pair[0] as isize)
-            .collect();
-        (diffs.iter().all(|d| d.is_positive()) || diffs.iter().all(|d| d.is_negative()))
-            && diffs.iter().all(|d| d.abs() > 0 && d.abs() < 4)
-    }
-
-    fn parse_input(input: &str) -> Vec<Vec<usize>> {
-        read_lines(input).iter()
-            .map(|line| parse_line(line))
-            .collect()
-    }
-
-
-    fn parse_line(line: &str) -> Vec<usize> {
-        line.split(" ")
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect()
+        let input = personal_input!("input_02");
+        assert_eq!(Ok(658), count_safe_report(&input, is_report_safe_with_fallback))
     }
-}
\ No newline at end of file
+}
@@ -1,279 +1,256 @@
-#[cfg(test)]
-mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
-    use indoc::indoc;
-    use std::collections::{HashMap, HashSet, VecDeque};
+use crate::geometry::{Direction, Point};
+use crate::parse_error::{lines, Line, ParseError};
+use crate::search::all_shortest_paths;
+use crate::solver::{Answer, Solver};
+use std::collections::{HashMap, HashSet};
 
-    #[test]
-    fn it_calculates_the_sum_of_complexity() {
-        let input = indoc! {"
-            029A
-            980A
-            179A
-            456A
-            379A
-        "};
+pub struct Day21;
 
-        assert_eq!(126384, sum_of_complexity(input, 2))
+impl Solver for Day21 {
+    type Input = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_codes(input)
     }
 
-    #[test]
-    fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_21");
+    fn part1(&self, codes: &Self::Input) -> Answer {
+        sum_complexity(codes, 2).into()
+    }
 
-        assert_eq!(237342, sum_of_complexity(input, 2));
-        assert_eq!(294585598101704, sum_of_complexity(input, 25));
+    fn part2(&self, codes: &Self::Input) -> Answer {
+        sum_complexity(codes, 25).into()
     }
+}
+
+type Keyboard = HashMap<char, Point>;
+type MinSequences = HashMap<(Point, Point), Vec<Vec<char>>>;
+type Memo = HashMap<(Vec<char>, usize), usize>;
+
+pub fn parse_codes(input: &str) -> Result<Vec<String>, ParseError> {
+    lines(21, input)
+        .map(|line| parse_code(&line))
+        .collect()
+}
 
-    fn sum_of_complexity(input: &str, levels: usize) -> usize {
-        let numeric_keyboard = HashMap::from([
-            ('7', (0, 0)),
-            ('8', (1, 0)),
-            ('9', (2, 0)),
-            ('4', (0, 1)),
-            ('5', (1, 1)),
-            ('6', (2, 1)),
-            ('1', (0, 2)),
-            ('2', (1, 2)),
-            ('3', (2, 2)),
-            ('0', (1, 3)),
-            ('A', (2, 3)),
-        ]);
-
-        let directional_keypad = HashMap::from([
-            ('^', (1, 0)),
-            ('A', (2, 0)),
-            ('<', (0, 1)),
-            ('v', (1, 1)),
-            ('>', (2, 1)),
-        ]);
-
-        let numeric_keyboard_min_sequences = find_min_sequences_on_keyboard(&numeric_keyboard, neighbours_numeric_keyboard);
-        let directional_keymap_min_sequences = find_min_sequences_on_keyboard(&directional_keypad, neighbours_directional_keypad);
-
-        let mut memo = HashMap::new();
-
-        read_lines(input)
-            .iter()
-            .map(|code| {
-                complexity(
-                    &numeric_keyboard,
-                    &directional_keypad,
-                    &numeric_keyboard_min_sequences,
-                    &directional_keymap_min_sequences,
-                    &mut memo,
-                    code,
-                    levels)
-            }).sum()
+fn parse_code(line: &Line) -> Result<String, ParseError> {
+    if let Some((index, _)) = line.text.char_indices().find(|(_, c)| !c.is_ascii_digit() && *c != 'A') {
+        return Err(line.error_at(&line.text[index..], "a numeric keypad button"));
     }
 
-    fn complexity(
-        numeric_keyboard: &HashMap<char, (isize, isize)>,
-        directional_keypad: &HashMap<char, (isize, isize)>,
-        numeric_keyboard_min_sequences: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>,
-        directional_keymap_min_sequences: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>,
-        mut memo: &mut HashMap<(Vec<char>, usize), usize>,
-        code: &str,
-        levels: usize,
-    ) -> usize {
-        let length_shortest_sequence = find_length_shortest_sequence(&numeric_keyboard, &directional_keypad, &numeric_keyboard_min_sequences, &directional_keymap_min_sequences, &mut memo, code, levels);
-        let numeric_part = code.chars().filter(|c| c.is_digit(10)).collect::<String>().parse::<usize>().unwrap();
-        length_shortest_sequence * numeric_part
+    if !line.text.chars().any(|c| c.is_ascii_digit()) {
+        return Err(line.error_at(line.text, "a code with a numeric part"));
     }
 
-    fn find_length_shortest_sequence(numeric_keyboard: &HashMap<char, (isize, isize)>, directional_keypad: &HashMap<char, (isize, isize)>, numeric_keyboard_min_sequences: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>, directional_keymap_min_sequences: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>, mut memo: &mut HashMap<(Vec<char>, usize), usize>, code: &str, levels: usize) -> usize {
-        let mut start = 'A';
-        let mut length_shortest_sequence = 0;
-        for end in code.chars() {
-            length_shortest_sequence += move_from_start_to_end(
+    Ok(line.text.to_string())
+}
+
+pub fn sum_of_complexity(input: &str, levels: usize) -> Result<usize, ParseError> {
+    Ok(sum_complexity(&parse_codes(input)?, levels))
+}
+
+fn sum_complexity(codes: &[String], levels: usize) -> usize {
+    let numeric_keyboard = HashMap::from([
+        ('7', Point::new(0, 0)),
+        ('8', Point::new(1, 0)),
+        ('9', Point::new(2, 0)),
+        ('4', Point::new(0, 1)),
+        ('5', Point::new(1, 1)),
+        ('6', Point::new(2, 1)),
+        ('1', Point::new(0, 2)),
+        ('2', Point::new(1, 2)),
+        ('3', Point::new(2, 2)),
+        ('0', Point::new(1, 3)),
+        ('A', Point::new(2, 3)),
+    ]);
+
+    let directional_keypad = HashMap::from([
+        ('^', Point::new(1, 0)),
+        ('A', Point::new(2, 0)),
+        ('<', Point::new(0, 1)),
+        ('v', Point::new(1, 1)),
+        ('>', Point::new(2, 1)),
+    ]);
+
+    let numeric_keyboard_min_sequences = find_min_sequences_on_keyboard(&numeric_keyboard);
+    let directional_keymap_min_sequences = find_min_sequences_on_keyboard(&directional_keypad);
+
+    let mut memo = HashMap::new();
+
+    codes
+        .iter()
+        .map(|code| {
+            complexity(
                 &numeric_keyboard,
                 &directional_keypad,
                 &numeric_keyboard_min_sequences,
                 &directional_keymap_min_sequences,
-                &end,
-                &start,
                 &mut memo,
-                levels);
-            start = end;
-        }
-        length_shortest_sequence
+                code,
+                levels)
+        }).sum()
+}
+
+fn complexity(
+    numeric_keyboard: &Keyboard,
+    directional_keypad: &Keyboard,
+    numeric_keyboard_min_sequences: &MinSequences,
+    directional_keymap_min_sequences: &MinSequences,
+    memo: &mut Memo,
+    code: &str,
+    levels: usize,
+) -> usize {
+    let length_shortest_sequence = find_length_shortest_sequence(numeric_keyboard, directional_keypad, numeric_keyboard_min_sequences, directional_keymap_min_sequences, memo, code, levels);
+    let numeric_part = code.chars().filter(|c| c.is_ascii_digit()).collect::<String>().parse::<usize>().unwrap();
+    length_shortest_sequence * numeric_part
+}
+
+fn find_length_shortest_sequence(numeric_keyboard: &Keyboard, directional_keypad: &Keyboard, numeric_keyboard_min_sequences: &MinSequences, directional_keymap_min_sequences: &MinSequences, memo: &mut Memo, code: &str, levels: usize) -> usize {
+    let mut start = 'A';
+    let mut length_shortest_sequence = 0;
+    for end in code.chars() {
+        length_shortest_sequence += move_from_start_to_end(
+            numeric_keyboard,
+            directional_keypad,
+            numeric_keyboard_min_sequences,
+            directional_keymap_min_sequences,
+            (start, end),
+            memo,
+            levels);
+        start = end;
     }
-
-    fn move_from_start_to_end
-    (numeric_keyboard: &HashMap<char, (isize, isize)>,
-     directional_keypad: &HashMap<char, (isize, isize)>,
-     numeric_keyboard_min_sequences: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>,
-     directional_keymap_min_sequences: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>,
-     end: &char,
-     start: &char,
-     memo: &mut HashMap<(Vec<char>, usize), usize>,
-     levels: usize,
-    ) -> usize {
-        let sequences = numeric_keyboard_min_sequences[&(numeric_keyboard[&start], numeric_keyboard[&end].clone())].clone();
-        let mut result = usize::MAX;
-        for sequence in sequences {
-            let r = sequence_length(sequence, levels, &directional_keypad, memo, &directional_keymap_min_sequences);
-            if r < result {
-                result = r;
-            }
+    length_shortest_sequence
+}
+
+fn move_from_start_to_end
+(numeric_keyboard: &Keyboard,
+ directional_keypad: &Keyboard,
+ numeric_keyboard_min_sequences: &MinSequences,
+ directional_keymap_min_sequences: &MinSequences,
+ (start, end): (char, char),
+ memo: &mut Memo,
+ levels: usize,
+) -> usize {
+    let sequences = numeric_keyboard_min_sequences[&(numeric_keyboard[&start], numeric_keyboard[&end])].clone();
+    let mut result = usize::MAX;
+    for sequence in sequences {
+        let r = sequence_length(sequence, levels, directional_keypad, memo, directional_keymap_min_sequences);
+        if r < result {
+            result = r;
         }
-        result
     }
-
-    fn sequence_length(
-        sequence: Vec<char>,
-        level: usize,
-        keymap_sequences: &HashMap<char, (isize, isize)>,
-        memo: &mut HashMap<(Vec<char>, usize), usize>,
-        cells_connections: &HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>>,
-    ) -> usize {
-        if level == 0 {
-            return sequence.len();
-        }
-
-        if let Some(result) = memo.get(&(sequence.clone(), level)) {
-            return *result;
-        }
-
-        let mut start = keymap_sequences[&'A'].clone();
-
-        let mut total = 0;
-        for e in &sequence {
-            let end = keymap_sequences[&e];
-            let mut min = usize::MAX;
-            for s in &cells_connections[&(start.clone(), end.clone())] {
-                let c = sequence_length(s.clone(), level - 1, keymap_sequences, memo, cells_connections);
-                if c < min {
-                    min = c;
-                }
-            }
-            total += min;
-            start = end;
-        }
-
-        memo.insert((sequence.clone(), level), total);
-        total
+    result
+}
+
+fn sequence_length(
+    sequence: Vec<char>,
+    level: usize,
+    keymap_sequences: &Keyboard,
+    memo: &mut Memo,
+    cells_connections: &MinSequences,
+) -> usize {
+    if level == 0 {
+        return sequence.len();
     }
 
-    fn find_min_sequences_on_keyboard(keyboard: &HashMap<char, (isize, isize)>, neighbour: fn((isize, isize)) -> Vec<((isize, isize), char)>) -> HashMap<((isize, isize), (isize, isize)), Vec<Vec<char>>> {
-        let mut sequence = HashMap::new();
-
-        for &s in keyboard.values() {
-            for &e in keyboard.values() {
-                let distance = find_sequence(s, e, neighbour);
-                sequence.insert((s, e), distance);
-            }
-        }
-
-        sequence
+    if let Some(result) = memo.get(&(sequence.clone(), level)) {
+        return *result;
     }
 
-    fn find_sequence(
-        start_position: (isize, isize),
-        end_position: (isize, isize),
-        neighbour: fn((isize, isize)) -> Vec<((isize, isize), char)>,
-    ) -> Vec<Vec<char>> {
-        let current_position = start_position;
-        let mut sequences = vec![];
-        let mut queue = VecDeque::from([(current_position, 0, vec![], HashSet::new())]);
-        let mut min_length = usize::MAX;
-
-        while let Some((p, index, s, visited)) = queue.pop_front() {
-            if s.len() > min_length {
-                continue;
-            }
-
-            if p == end_position {
-                let mut copy = s.clone();
-                copy.push('A');
-                min_length = copy.len();
-                sequences.push(copy);
-                continue;
-            }
+    let mut start = keymap_sequences[&'A'];
 
-            for (n, step) in neighbour(p) {
-                if visited.contains(&n) {
-                    continue;
-                }
-                let mut c = s.clone();
-                c.push(step);
-                let mut vc = visited.clone();
-                vc.insert(p);
-                queue.push_back((n, index, c, vc))
+    let mut total = 0;
+    for e in &sequence {
+        let end = keymap_sequences[e];
+        let mut min = usize::MAX;
+        for s in &cells_connections[&(start, end)] {
+            let c = sequence_length(s.clone(), level - 1, keymap_sequences, memo, cells_connections);
+            if c < min {
+                min = c;
             }
         }
-
-        sequences
+        total += min;
+        start = end;
     }
 
-    fn neighbours_directional_keypad((x, y): (isize, isize)) -> Vec<((isize, isize), char)> {
-        if (x, y) == (1, 0) {
-            return vec![
-                ((2, 0), '>'),
-                ((1, 1), 'v'),
-            ];
-        }
-
-        if (x, y) == (0, 1) {
-            return vec![
-                ((1, 1), '>')
-            ];
-        }
-
-        let mut result = vec![];
-
-        if x != 0 {
-            result.push(((x - 1, y), '<'))
-        }
-
-        if x != 2 {
-            result.push(((x + 1, y), '>'))
-        }
+    memo.insert((sequence.clone(), level), total);
+    total
+}
 
-        if y != 0 {
-            result.push(((x, y - 1), '^'))
-        }
+fn find_min_sequences_on_keyboard(keyboard: &Keyboard) -> MinSequences {
+    let mut sequence = HashMap::new();
 
-        if y != 1 {
-            result.push(((x, y + 1), 'v'))
+    for &s in keyboard.values() {
+        for &e in keyboard.values() {
+            let distance = find_sequence(keyboard, s, e);
+            sequence.insert((s, e), distance);
         }
-
-        result
     }
 
-    fn neighbours_numeric_keyboard((x, y): (isize, isize)) -> Vec<((isize, isize), char)> {
-        if (x, y) == (1, 3) {
-            return vec![
-                ((x, y - 1), '^'),
-                ((x + 1, y), '>')
-            ];
-        }
-
-        if (x, y) == (0, 2) {
-            return vec![
-                ((0, 1), '^'),
-                ((1, 2), '>')
-            ];
-        }
+    sequence
+}
+
+// Every shortest sequence of arrows moving from start to end without hovering the gap, followed by 'A'
+fn find_sequence(keyboard: &Keyboard, start_position: Point, end_position: Point) -> Vec<Vec<char>> {
+    let keys: HashSet<_> = keyboard.values().collect();
+
+    let paths = all_shortest_paths(
+        [start_position],
+        |&p| {
+            Direction::ALL.into_iter()
+                .map(move |d| (p.step(d), 1))
+                .filter(|(n, _)| keys.contains(n))
+        },
+        |&p| p == end_position,
+    );
+
+    paths.paths_to(&end_position)
+        .iter()
+        .map(|path| {
+            path.windows(2)
+                .map(|step| arrow(step[0], step[1]))
+                .chain(['A'])
+                .collect()
+        })
+        .collect()
+}
+
+fn arrow(from: Point, to: Point) -> char {
+    Direction::ALL.into_iter()
+        .find(|d| from.step(*d) == to)
+        .map(Direction::to_char)
+        .expect("keys of a path are next to each other")
+}
 
-        let mut result = vec![];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personal_input;
+    use indoc::indoc;
 
-        if x != 0 {
-            result.push(((x - 1, y), '<'));
-        }
+    #[test]
+    fn it_calculates_the_sum_of_complexity() {
+        let input = indoc! {"
+            029A
+            980A
+            179A
+            456A
+            379A
+        "};
 
-        if x != 2 {
-            result.push(((x + 1, y), '>'));
-        }
+        assert_eq!(Ok(126384), sum_of_complexity(input, 2))
+    }
 
-        if y != 0 {
-            result.push(((x, y - 1), '^'));
-        }
+    #[test]
+    fn it_reports_invalid_codes() {
+        assert_eq!(Err(ParseError::new(21, 2, 3, "a numeric keypad button")), parse_codes("029A\n98BA"));
+        assert_eq!(Err(ParseError::new(21, 1, 1, "a code with a numeric part")), parse_codes("A"));
+    }
 
-        if y != 3 {
-            result.push(((x, y + 1), 'v'));
-        }
+    #[test]
+    fn it_solves_first_puzzle() {
+        let input = &personal_input!("input_21");
 
-        result
+        assert_eq!(Ok(237342), sum_of_complexity(input, 2));
+        assert_eq!(Ok(294585598101704), sum_of_complexity(input, 25));
     }
-}
\ No newline at end of file
+}
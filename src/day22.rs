@@ -1,8 +1,112 @@
+use crate::parse_error::{lines, ParseError};
+use crate::solver::{Answer, Solver};
+use std::collections::{HashMap, HashSet};
+
+pub struct Day22;
+
+impl Solver for Day22 {
+    type Input = Vec<usize>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_secret_numbers(input)
+    }
+
+    fn part1(&self, secret_numbers: &Self::Input) -> Answer {
+        sum_of_the_2000th_secret_number(secret_numbers.clone()).into()
+    }
+
+    fn part2(&self, secret_numbers: &Self::Input) -> Answer {
+        most_bananas(secret_numbers).into()
+    }
+}
+
+pub fn parse_secret_numbers(input: &str) -> Result<Vec<usize>, ParseError> {
+    lines(22, input)
+        .map(|line| line.parse::<usize>(line.text, "a secret number"))
+        .collect()
+}
+
+pub fn calculate_most_bananas(input: &str) -> Result<usize, ParseError> {
+    Ok(most_bananas(&parse_secret_numbers(input)?))
+}
+
+fn most_bananas(secret_numbers: &[usize]) -> usize {
+    let times_to_evolve = 2000;
+
+    let all_changes = secret_numbers.iter()
+        .map(|&secret_code| calculate_changes(times_to_evolve, secret_code))
+        .collect::<Vec<_>>();
+
+    let all_combinations = all_changes.iter()
+        .fold(HashSet::new(), |mut acc, current| {
+            acc.extend(current.keys());
+            acc
+        });
+
+    all_combinations
+        .iter()
+        .map(|&combination| {
+            all_changes.iter()
+                .map(|changes|
+                    changes.get(combination).unwrap_or(&0)
+                ).sum()
+        }).max()
+        .unwrap_or(0)
+}
+
+fn calculate_changes(times_to_evolve: i32, secret_code: usize) -> HashMap<Vec<isize>, usize> {
+    let (_, bananas, deltas) = (0..times_to_evolve)
+        .fold(
+            (secret_code, vec![], vec![]),
+            |(prev, mut bananas, mut deltas), _| {
+                let next = evolve(prev);
+                bananas.push(next % 10);
+                deltas.push((next % 10) as isize - (prev % 10) as isize);
+                (next, bananas, deltas)
+            });
+
+    let mut result = HashMap::new();
+
+    for (index, w) in deltas.windows(4).enumerate() {
+        let key = w.to_vec();
+
+        if result.contains_key(&key) {
+            continue;
+        }
+
+        result.insert(key, bananas[index + 3]);
+    }
+
+    result
+}
+
+pub fn calculate_sum_of_the_2000th_generated_secret_number(input: &str) -> Result<usize, ParseError> {
+    Ok(sum_of_the_2000th_secret_number(parse_secret_numbers(input)?))
+}
+
+fn sum_of_the_2000th_secret_number(mut secret_numbers: Vec<usize>) -> usize {
+    for _ in 0..2000 {
+        for secret_number in secret_numbers.iter_mut() {
+            *secret_number = evolve(*secret_number)
+        }
+    }
+
+    let sum: usize = secret_numbers.iter().sum();
+    sum
+}
+
+fn evolve(secret_number: usize) -> usize {
+    let mut secret_number = ((secret_number << 6) ^ secret_number) % 16777216;
+    secret_number = ((secret_number >> 5) ^ secret_number) % 16777216;
+    secret_number = ((secret_number << 11) ^ secret_number) % 16777216;
+    secret_number
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use std::collections::{HashMap, HashSet};
 
     #[test]
     fn it_evolve_a_secret_number() {
@@ -18,14 +122,19 @@ mod tests {
         2024
         "};
 
-        assert_eq!(37327623, calculate_sum_of_the_2000th_generated_secret_number(input))
+        assert_eq!(Ok(37327623), calculate_sum_of_the_2000th_generated_secret_number(input))
+    }
+
+    #[test]
+    fn it_reports_invalid_secret_numbers() {
+        assert_eq!(Err(ParseError::new(22, 2, 1, "a secret number")), parse_secret_numbers("1\nten"));
     }
 
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_22");
+        let input = &personal_input!("input_22");
 
-        assert_eq!(20401393616, calculate_sum_of_the_2000th_generated_secret_number(input))
+        assert_eq!(Ok(20401393616), calculate_sum_of_the_2000th_generated_secret_number(input))
     }
 
     #[test]
@@ -37,88 +146,13 @@ mod tests {
         2024
         "};
 
-        assert_eq!(23, calculate_most_bananas(input));
+        assert_eq!(Ok(23), calculate_most_bananas(input));
     }
 
     #[test]
     fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_22");
-
-        assert_eq!(2272, calculate_most_bananas(input)); // slow ~47 seconds
-    }
-
-    fn calculate_most_bananas(input: &str) -> usize {
-        let times_to_evolve = 2000;
-
-        let all_changes = read_lines(input).iter()
-            .map(|raw_secret_code| {
-                let secret_code = raw_secret_code.parse::<usize>().unwrap();
-                calculate_changes(times_to_evolve, secret_code)
-            })
-            .collect::<Vec<_>>();
-
-        let all_combinations = all_changes.iter()
-            .fold(HashSet::new(), |mut acc, current| {
-                acc.extend(current.keys());
-                acc
-            });
-
-        all_combinations
-            .iter()
-            .map(|&combination| {
-                all_changes.iter()
-                    .map(|changes|
-                        changes.get(combination).unwrap_or(&0)
-                    ).sum()
-            }).max()
-            .unwrap()
-    }
-
-    fn calculate_changes(times_to_evolve: i32, secret_code: usize) -> HashMap<Vec<isize>, usize> {
-        let (_, bananas, deltas) = (0..times_to_evolve)
-            .fold(
-                (secret_code, vec![], vec![]),
-                |(prev, mut bananas, mut deltas), _| {
-                    let next = evolve(prev);
-                    bananas.push(next % 10);
-                    deltas.push((next % 10) as isize - (prev % 10) as isize);
-                    (next, bananas, deltas)
-                });
-
-        let mut result = HashMap::new();
-
-        for (index, w) in deltas.windows(4).enumerate() {
-            let key = w.to_vec();
-
-            if result.contains_key(&key) {
-                continue;
-            }
-
-            result.insert(key, bananas[index + 3]);
-        }
-
-        result
-    }
-
-    fn calculate_sum_of_the_2000th_generated_secret_number(input: &str) -> usize {
-        let mut secret_numbers: Vec<usize> = read_lines(input).iter()
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect();
-
-        for _ in 0..2000 {
-            for i in 0..secret_numbers.len() {
-                secret_numbers[i] = evolve(secret_numbers[i])
-            }
-        }
-
-        let sum: usize = secret_numbers.iter().sum();
-        sum
-    }
+        let input = &personal_input!("input_22");
 
-    fn evolve(secret_number: usize) -> usize {
-        let mut secret_number = ((secret_number << 6) ^ secret_number) % 16777216;
-        secret_number = ((secret_number >> 5) ^ secret_number) % 16777216;
-        secret_number = ((secret_number << 11) ^ secret_number) % 16777216;
-        secret_number
+        assert_eq!(Ok(2272), calculate_most_bananas(input)); // slow ~47 seconds
     }
-}
\ No newline at end of file
+}
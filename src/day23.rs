@@ -1,9 +1,106 @@
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+pub struct Day23;
+
+impl Solver for Day23 {
+    type Input = HashMap<String, HashSet<String>>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_connections(input)
+    }
+
+    fn part1(&self, connections: &Self::Input) -> Answer {
+        let all = combinations(connections, 3);
+        count_combinations_starting_with_t(&all).into()
+    }
+
+    fn part2(&self, connections: &Self::Input) -> Answer {
+        find_largest_set_of_computers(connections).into()
+    }
+}
+
+pub fn find_largest_set_of_computers(connections: &HashMap<String, HashSet<String>>) -> String {
+    let mut result = vec![];
+
+    for connection in connections.values() {
+        let combinations = connection.iter()
+            .combinations(2)
+            .map(|c| connections[c[0]].intersection(&connections[c[1]]).sorted().collect::<Vec<_>>())
+            .collect::<HashSet<_>>();
+
+        for combination in combinations {
+            let intersection = combination.iter()
+                .map(|&c| connections[c].clone())
+                .reduce(|a, b| a.intersection(&b).map(|v| v.to_string()).collect::<HashSet<_>>())
+                .unwrap();
+            let sorted_intersection = intersection
+                .iter()
+                .sorted()
+                .collect_vec();
+
+            if sorted_intersection == combination && sorted_intersection.len() > result.len() {
+                result = combination;
+            }
+        }
+    }
+    result.into_iter().join(",")
+}
+
+pub fn count_combinations_starting_with_t(all: &HashSet<Vec<String>>) -> usize {
+    let mut result = 0;
+    for s in all {
+        if s.iter().any(|p| p.starts_with("t")) {
+            result += 1;
+        }
+    }
+    result
+}
+
+pub fn combinations(connections: &HashMap<String, HashSet<String>>, length: usize) -> HashSet<Vec<String>> {
+    let mut all = HashSet::new();
+    for v in connections.values() {
+        for c2 in v.iter().combinations(length) {
+            let set = HashSet::from_iter(c2.clone().iter().map(|s| s.to_string()));
+            if c2.iter().all(|&c3| connections[c3].intersection(&set).count() == length) {
+                let vec = set.iter().sorted().map(|v| v.to_string()).collect::<Vec<_>>();
+                all.insert(vec);
+            }
+        }
+    }
+    all
+}
+
+pub fn parse_connections(input: &str) -> Result<HashMap<String, HashSet<String>>, ParseError> {
+    let mut connections = HashMap::new();
+    for line in lines(23, input) {
+        let (first, second) = line.split_once("-")?;
+        let first = parse_computer(&line, first)?;
+        let second = parse_computer(&line, second)?;
+        connections.entry(first.clone())
+            .and_modify(|v: &mut HashSet<String>| { v.insert(second.clone()); })
+            .or_insert(HashSet::from([first.clone(), second.clone()]));
+        connections.entry(second.clone())
+            .and_modify(|v: &mut HashSet<String>| { v.insert(first.clone()); })
+            .or_insert(HashSet::from([second, first]));
+    }
+    Ok(connections)
+}
+
+fn parse_computer(line: &Line, computer: &str) -> Result<String, ParseError> {
+    if computer.len() != 2 || !computer.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(line.error_at(computer, "a computer name of two lowercase letters"));
+    }
+    Ok(computer.to_string())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use itertools::Itertools;
-    use std::collections::{HashMap, HashSet};
 
     #[test]
     fn it_counts_combinations_starting_with_t() {
@@ -42,7 +139,7 @@ mod tests {
             td-yn
         "};
 
-        let connections = parse_connections(input);
+        let connections = parse_connections(input).unwrap();
         let all = combinations(&connections, 3);
         assert_eq!(7, count_combinations_starting_with_t(&all));
         assert_eq!("co,de,ka,ta", find_largest_set_of_computers(&connections));
@@ -85,91 +182,33 @@ mod tests {
             td-yn
         "};
 
-        let connections = parse_connections(input);
+        let connections = parse_connections(input).unwrap();
         assert_eq!("co,de,ka,ta", find_largest_set_of_computers(&connections));
     }
 
+    #[test]
+    fn it_reports_invalid_connections() {
+        assert_eq!(Some(ParseError::new(23, 2, 6, "'-'")), parse_connections("kh-tc\nqp kh").err());
+        assert_eq!(
+            Some(ParseError::new(23, 1, 4, "a computer name of two lowercase letters")),
+            parse_connections("kh-TC").err()
+        );
+    }
+
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_23");
+        let input = &personal_input!("input_23");
 
-        let connections = parse_connections(input);
+        let connections = parse_connections(input).unwrap();
         let all = combinations(&connections, 3);
         assert_eq!(1184, count_combinations_starting_with_t(&all));
     }
 
     #[test]
     fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_23");
+        let input = &personal_input!("input_23");
 
-        let connections = parse_connections(input);
+        let connections = parse_connections(input).unwrap();
         assert_eq!("hf,hz,lb,lm,ls,my,ps,qu,ra,uc,vi,xz,yv", find_largest_set_of_computers(&connections));
     }
-
-    fn find_largest_set_of_computers(connections: &HashMap<String, HashSet<String>>) -> String {
-        let mut result = vec![];
-
-        for connection in connections.values() {
-            let combinations = connection.into_iter()
-                .combinations(2)
-                .map(|c| connections[c[0]].intersection(&connections[c[1]]).sorted().collect::<Vec<_>>())
-                .collect::<HashSet<_>>();
-
-            for combination in combinations {
-                let intersection = combination.iter()
-                    .map(|&c| connections[c].clone())
-                    .reduce(|a, b| a.intersection(&b).map(|v| v.to_string()).collect::<HashSet<_>>())
-                    .unwrap();
-                let sorted_intersection = intersection
-                    .iter()
-                    .sorted()
-                    .collect_vec();
-
-                if sorted_intersection == combination && sorted_intersection.len() > result.len() {
-                    result = combination;
-                }
-            }
-        }
-        result.into_iter().join(",")
-    }
-
-    fn count_combinations_starting_with_t(all: &HashSet<Vec<String>>) -> i32 {
-        let mut result = 0;
-        for s in all {
-            if s.iter().any(|p| p.starts_with("t")) {
-                result += 1;
-            }
-        }
-        result
-    }
-
-    fn combinations(connections: &HashMap<String, HashSet<String>>, length: usize) -> HashSet<Vec<String>> {
-        let mut all = HashSet::new();
-        for (_c1, v) in connections {
-            for c2 in v.iter().combinations(length) {
-                let set = HashSet::from_iter(c2.clone().iter().map(|s| s.to_string()));
-                if c2.iter().all(|&c3| connections[c3].intersection(&set).count() == length) {
-                    let vec = set.iter().sorted().map(|v| v.to_string()).collect::<Vec<_>>();
-                    all.insert(vec);
-                }
-            }
-        }
-        all
-    }
-
-    fn parse_connections(input: &str) -> HashMap<String, HashSet<String>> {
-        let mut connections = HashMap::new();
-        for line in read_lines(input) {
-            let parts: Vec<_> = line.split("-").collect();
-            let first = parts[0].to_string();
-            let second = parts[1].to_string();
-            connections.entry(first.clone())
-                .and_modify(|v: &mut HashSet<String>| { v.insert(second.clone()); })
-                .or_insert(HashSet::from([first.clone(), second.clone()]));
-            connections.entry(second.clone())
-                .and_modify(|v: &mut HashSet<String>| { v.insert(first.clone()); })
-                .or_insert(HashSet::from([second, first]));
-        }
-        connections
-    }
-}
\ No newline at end of file
+}
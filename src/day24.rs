@@ -1,7 +1,413 @@
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+use itertools::Itertools;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+pub struct Day24;
+
+impl Solver for Day24 {
+    type Input = Circuit;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_circuit(input)
+    }
+
+    fn part1(&self, circuit: &Self::Input) -> Answer {
+        circuit.simulate()
+            .ok_or(CircuitError::Unsettled)
+            .and_then(|wires| output_number(&wires, 'z'))
+            .into()
+    }
+
+    fn part2(&self, circuit: &Self::Input) -> Answer {
+        swapped_wires(circuit).into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    // A gate reads a wire nobody drives or the gates form a loop
+    Unsettled,
+    // The number has a bit that does not fit in 64 bits
+    TooWide(String),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Unsettled => write!(f, "the circuit never settles"),
+            CircuitError::TooWide(wire) => write!(f, "wire {} does not fit in a 64 bits number", wire),
+        }
+    }
+}
+
+impl Error for CircuitError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    And,
+    Or,
+    Xor,
+}
+
+impl Operation {
+    fn apply(&self, left: bool, right: bool) -> bool {
+        match self {
+            Operation::And => left && right,
+            Operation::Or => left || right,
+            Operation::Xor => left ^ right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gate {
+    pub left: String,
+    pub operation: Operation,
+    pub right: String,
+    pub output: String,
+}
+
+impl Gate {
+    fn has_input(&self, wire: &str) -> bool {
+        self.left == wire || self.right == wire
+    }
+
+    fn reads_inputs(&self) -> bool {
+        [&self.left, &self.right].iter().all(|w| w.starts_with('x') || w.starts_with('y'))
+    }
+
+    fn reads_first_bit(&self) -> bool {
+        [&self.left, &self.right].iter().all(|w| *w == "x00" || *w == "y00")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit {
+    pub wires: HashMap<String, bool>,
+    pub gates: Vec<Gate>,
+}
+
+impl Circuit {
+    // Gates ordered so that both inputs of a gate are known before it is evaluated, None when a
+    // gate reads a wire nobody drives or the gates form a loop
+    pub fn topological_order(&self) -> Option<Vec<&Gate>> {
+        let mut pending_inputs: Vec<usize> = self.gates.iter()
+            .map(|g| [&g.left, &g.right].iter().filter(|w| !self.wires.contains_key(w.as_str())).count())
+            .collect();
+
+        let mut readers: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, gate) in self.gates.iter().enumerate() {
+            readers.entry(&gate.left).or_default().push(index);
+            readers.entry(&gate.right).or_default().push(index);
+        }
+
+        let mut ready: VecDeque<usize> = (0..self.gates.len()).filter(|&i| pending_inputs[i] == 0).collect();
+        let mut order = vec![];
+        while let Some(index) = ready.pop_front() {
+            let gate = &self.gates[index];
+            order.push(gate);
+
+            for &reader in readers.get(gate.output.as_str()).into_iter().flatten() {
+                pending_inputs[reader] -= 1;
+                if pending_inputs[reader] == 0 {
+                    ready.push_back(reader);
+                }
+            }
+        }
+
+        (order.len() == self.gates.len()).then_some(order)
+    }
+
+    pub fn simulate(&self) -> Option<HashMap<String, bool>> {
+        let mut wires = self.wires.clone();
+        for gate in self.topological_order()? {
+            let value = gate.operation.apply(wires[&gate.left], wires[&gate.right]);
+            wires.insert(gate.output.clone(), value);
+        }
+        Some(wires)
+    }
+}
+
+// Bit 0 is the wire ending with 00
+pub fn output_number(wires: &HashMap<String, bool>, prefix: char) -> Result<usize, CircuitError> {
+    let mut number = 0;
+    for (name, &value) in wires.iter().filter(|(name, _)| name.starts_with(prefix)) {
+        let Ok(bit) = name[1..].parse::<u32>() else {
+            continue;
+        };
+
+        let mask = 1_usize.checked_shl(bit).ok_or_else(|| CircuitError::TooWide(name.clone()))?;
+        if value {
+            number |= mask;
+        }
+    }
+    Ok(number)
+}
+
+// In a ripple-carry adder z00 is x00 XOR y00, every other z wire but the last is the XOR of the sum
+// bit and the carry, the last one is the final carry. Sum bits (x XOR y) feed an XOR, half carries (x AND y, sum AND
+// carry) feed the OR computing the next carry. Gates that break these rules have a swapped output.
+pub fn swapped_wires(circuit: &Circuit) -> String {
+    let last_z = circuit.gates.iter()
+        .map(|g| &g.output)
+        .filter(|w| w.starts_with('z'))
+        .max();
+    let feeds = |wire: &str, operation: Operation| {
+        circuit.gates.iter().any(|g| g.operation == operation && g.has_input(wire))
+    };
+
+    let mut swapped = BTreeSet::new();
+    for gate in &circuit.gates {
+        let outputs_z = gate.output.starts_with('z');
+        let is_wrong = match gate.operation {
+            _ if outputs_z && Some(&gate.output) == last_z => gate.operation != Operation::Or,
+            Operation::Xor if gate.reads_first_bit() => gate.output != "z00",
+            Operation::Xor if gate.reads_inputs() => !feeds(&gate.output, Operation::Xor),
+            Operation::Xor => !outputs_z,
+            Operation::And => outputs_z || (!gate.reads_first_bit() && !feeds(&gate.output, Operation::Or)),
+            Operation::Or => outputs_z,
+        };
+
+        if is_wrong {
+            swapped.insert(gate.output.as_str());
+        }
+    }
+
+    swapped.iter().join(",")
+}
+
+pub fn parse_circuit(input: &str) -> Result<Circuit, ParseError> {
+    let mut lines = lines(24, input);
+
+    let mut wires = HashMap::new();
+    for line in lines.by_ref() {
+        if line.text.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(": ")?;
+        let name = parse_wire(&line, name)?;
+        let value = match value {
+            "0" => false,
+            "1" => true,
+            _ => return Err(line.error_at(value, "0 or 1")),
+        };
+        wires.insert(name, value);
+    }
+
+    let gates = lines
+        .map(|line| parse_gate(&line))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Circuit { wires, gates })
+}
+
+fn parse_gate(line: &Line) -> Result<Gate, ParseError> {
+    let (inputs, output) = line.split_once(" -> ")?;
+
+    let missing = |expected| ParseError::new(24, line.number, inputs.len() + 1, expected);
+
+    let mut tokens = inputs.split(' ');
+    let left = parse_wire(line, tokens.next().unwrap_or(inputs))?;
+    let operation = match tokens.next() {
+        Some("AND") => Operation::And,
+        Some("OR") => Operation::Or,
+        Some("XOR") => Operation::Xor,
+        Some(token) => return Err(line.error_at(token, "AND, OR or XOR")),
+        None => return Err(missing("AND, OR or XOR")),
+    };
+    let right = parse_wire(line, tokens.next().ok_or(missing("a wire name"))?)?;
+    if let Some(token) = tokens.next() {
+        return Err(line.error_at(token, "' -> '"));
+    }
+
+    Ok(Gate { left, operation, right, output: parse_wire(line, output)? })
+}
+
+fn parse_wire(line: &Line, wire: &str) -> Result<String, ParseError> {
+    if wire.is_empty() || !wire.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(line.error_at(wire, "a wire name"));
+    }
+    Ok(wire.to_string())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::personal_input;
+    use indoc::indoc;
+
+    // Adds the 3 bits numbers x and y, the gates are listed out of order on purpose
+    const ADDER: &str = indoc! {"
+        x00: 1
+        x01: 0
+        x02: 1
+        y00: 1
+        y01: 1
+        y02: 0
+
+        s02 XOR c01 -> z02
+        a02 OR b02 -> z03
+        x00 XOR y00 -> z00
+        s01 AND c00 -> b01
+        x00 AND y00 -> c00
+        x01 XOR y01 -> s01
+        s02 AND c01 -> b02
+        x01 AND y01 -> a01
+        s01 XOR c00 -> z01
+        a01 OR b01 -> c01
+        x02 XOR y02 -> s02
+        x02 AND y02 -> a02
+    "};
+
     #[test]
     fn it_parses_the_input() {
-        assert_eq!(false, true)
+        let circuit = parse_circuit("x00: 1\ny00: 0\n\nx00 AND y00 -> z00").unwrap();
+
+        assert_eq!(HashMap::from([("x00".to_string(), true), ("y00".to_string(), false)]), circuit.wires);
+        assert_eq!(
+            vec![Gate {
+                left: "x00".to_string(),
+                operation: Operation::And,
+                right: "y00".to_string(),
+                output: "z00".to_string(),
+            }],
+            circuit.gates
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_wires_and_gates() {
+        assert_eq!(Err(ParseError::new(24, 1, 6, "0 or 1")), parse_circuit("x00: 2\n\nx00 OR x00 -> z00"));
+        assert_eq!(Err(ParseError::new(24, 3, 5, "AND, OR or XOR")), parse_circuit("x00: 1\n\nx00 NOR x00 -> z00"));
+        assert_eq!(Err(ParseError::new(24, 3, 18, "' -> '")), parse_circuit("x00: 1\n\nx00 OR x00 => z00"));
+        assert_eq!(Err(ParseError::new(24, 3, 15, "a wire name")), parse_circuit("x00: 1\n\nx00 OR x00 -> z-0"));
+        assert_eq!(Err(ParseError::new(24, 3, 8, "a wire name")), parse_circuit("x00: 1\n\nx00 AND -> z00"));
+    }
+
+    #[test]
+    fn it_solves_the_first_puzzle_examples() {
+        let circuit = parse_circuit(indoc! {"
+            x00: 1
+            x01: 1
+            x02: 1
+            y00: 0
+            y01: 1
+            y02: 0
+
+            x00 AND y00 -> z00
+            x01 XOR y01 -> z01
+            x02 OR y02 -> z02
+        "}).unwrap();
+        assert_eq!(Answer::Number(4), Day24.part1(&circuit));
+
+        let circuit = parse_circuit(indoc! {"
+            x00: 1
+            x01: 0
+            x02: 1
+            x03: 1
+            x04: 0
+            y00: 1
+            y01: 1
+            y02: 1
+            y03: 1
+            y04: 1
+
+            ntg XOR fgs -> mjb
+            y02 OR x01 -> tnw
+            kwq OR kpj -> z05
+            x00 OR x03 -> fst
+            tgd XOR rvg -> z01
+            vdt OR tnw -> bfw
+            bfw AND frj -> z10
+            ffh OR nrd -> bqk
+            y00 AND y03 -> djm
+            y03 OR y00 -> psh
+            bqk OR frj -> z08
+            tnw OR fst -> frj
+            gnj AND tgd -> z11
+            bfw XOR mjb -> z00
+            x03 OR x00 -> vdt
+            gnj AND wpb -> z02
+            x04 AND y00 -> kjc
+            djm OR pbm -> qhw
+            nrd AND vdt -> hwm
+            kjc AND fst -> rvg
+            y04 OR y02 -> fgs
+            y01 AND x02 -> pbm
+            ntg OR kjc -> kwq
+            psh XOR fgs -> tgd
+            qhw XOR tgd -> z09
+            pbm OR djm -> kpj
+            x03 XOR y03 -> ffh
+            x00 XOR y04 -> ntg
+            bfw OR bqk -> z06
+            nrd XOR fgs -> wpb
+            frj XOR qhw -> z04
+            bqk OR frj -> z07
+            y03 OR x01 -> nrd
+            hwm AND bqk -> z03
+            tgd XOR rvg -> z12
+            tnw OR pbm -> gnj
+        "}).unwrap();
+        assert_eq!(Answer::Number(2024), Day24.part1(&circuit));
+    }
+
+    #[test]
+    fn it_simulates_the_circuit() {
+        let wires = parse_circuit(ADDER).unwrap().simulate().unwrap();
+        assert_eq!(
+            (Ok(5), Ok(3), Ok(8)),
+            (output_number(&wires, 'x'), output_number(&wires, 'y'), output_number(&wires, 'z'))
+        );
+    }
+
+    #[test]
+    fn it_refuses_numbers_wider_than_64_bits() {
+        let mut wires = HashMap::from([("z63".to_string(), true)]);
+        assert_eq!(Ok(1 << 63), output_number(&wires, 'z'));
+
+        wires.insert("z64".to_string(), false);
+        assert_eq!(Err(CircuitError::TooWide("z64".to_string())), output_number(&wires, 'z'));
+    }
+
+    #[test]
+    fn it_refuses_to_simulate_loops_and_undriven_wires() {
+        assert_eq!(None, parse_circuit("x00: 1\n\nx00 AND a -> b\nx00 OR b -> a").unwrap().simulate());
+        assert_eq!(None, parse_circuit("x00: 1\n\nx00 AND y00 -> z00").unwrap().simulate());
+    }
+
+    #[test]
+    fn it_finds_the_swapped_wires() {
+        assert_eq!("", swapped_wires(&parse_circuit(ADDER).unwrap()));
+
+        let swapped = ADDER
+            .replace("s01 XOR c00 -> z01", "s01 XOR c00 -> c01")
+            .replace("a01 OR b01 -> c01", "a01 OR b01 -> z01")
+            .replace("s02 XOR c01 -> z02", "s02 XOR c01 -> b02")
+            .replace("s02 AND c01 -> b02", "s02 AND c01 -> z02");
+
+        assert_eq!("b02,c01,z01,z02", swapped_wires(&parse_circuit(&swapped).unwrap()));
+    }
+
+    #[test]
+    fn it_checks_that_the_first_bits_are_added_into_z00() {
+        let swapped = ADDER
+            .replace("x00 XOR y00 -> z00", "x00 XOR y00 -> c00")
+            .replace("x00 AND y00 -> c00", "x00 AND y00 -> z00");
+
+        assert_eq!("c00,z00", swapped_wires(&parse_circuit(&swapped).unwrap()));
+    }
+
+    #[test]
+    fn it_solves_both_puzzles() {
+        let input = &personal_input!("input_24");
+        let circuit = parse_circuit(input).unwrap();
+
+        assert!(circuit.simulate().is_some());
+        assert_eq!(8, swapped_wires(&circuit).split(',').count());
     }
-}
\ No newline at end of file
+}
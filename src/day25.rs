@@ -0,0 +1,208 @@
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
+
+pub struct Day25;
+
+impl Solver for Day25 {
+    type Input = Schematics;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_schematics(input)
+    }
+
+    fn part1(&self, schematics: &Self::Input) -> Answer {
+        schematics.count_fitting_pairs().into()
+    }
+
+    fn part2(&self, _: &Self::Input) -> Answer {
+        Answer::Unsolved
+    }
+
+    // The last star is given for free once all the other ones are collected
+    fn has_part2(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematics {
+    pub locks: Vec<Vec<usize>>,
+    pub keys: Vec<Vec<usize>>,
+    pub space: usize,
+}
+
+impl Schematics {
+    pub fn count_fitting_pairs(&self) -> usize {
+        self.locks.iter()
+            .flat_map(|lock| self.keys.iter().map(move |key| (lock, key)))
+            .filter(|(lock, key)| self.fits(lock, key))
+            .count()
+    }
+
+    fn fits(&self, lock: &[usize], key: &[usize]) -> bool {
+        lock.iter().zip(key).all(|(l, k)| l + k <= self.space)
+    }
+}
+
+pub fn count_fitting_pairs(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_schematics(input)?.count_fitting_pairs())
+}
+
+// Locks have their top row filled and keys their bottom row, heights do not count the filled row.
+// Every schematic has the rows and columns of the first one.
+pub fn parse_schematics(input: &str) -> Result<Schematics, ParseError> {
+    let mut schematics = Schematics { locks: vec![], keys: vec![], space: 0 };
+    let mut dimensions = None;
+
+    let lines: Vec<_> = lines(25, input).collect();
+    for block in lines.split(|line| line.text.is_empty()).filter(|block| !block.is_empty()) {
+        let (is_lock, heights) = parse_schematic(block)?;
+
+        let (rows, columns) = *dimensions.get_or_insert((block.len(), heights.len()));
+        if heights.len() != columns {
+            let first = &block[0];
+            let column = heights.len().min(columns) + 1;
+            return Err(ParseError::new(25, first.number, column, format!("a row of {} columns", columns)));
+        }
+        if block.len() != rows {
+            let line = block.get(rows).unwrap_or(&block[block.len() - 1]);
+            return Err(ParseError::new(25, line.number, 1, format!("a schematic of {} rows", rows)));
+        }
+        schematics.space = rows - 2;
+
+        if is_lock {
+            schematics.locks.push(heights);
+        } else {
+            schematics.keys.push(heights);
+        }
+    }
+
+    Ok(schematics)
+}
+
+fn parse_schematic(block: &[Line]) -> Result<(bool, Vec<usize>), ParseError> {
+    let (first, last) = (&block[0], &block[block.len() - 1]);
+    if block.len() < 2 {
+        return Err(first.error_at_end("a schematic of at least 2 rows"));
+    }
+
+    let width = first.text.chars().count();
+    let mut heights = vec![0; width];
+    for line in block {
+        for (x, char) in line.text.chars().enumerate() {
+            match char {
+                '#' if x < width => heights[x] += 1,
+                '.' if x < width => {}
+                '#' | '.' => return Err(ParseError::new(25, line.number, x + 1, format!("a row of {} columns", width))),
+                _ => return Err(ParseError::new(25, line.number, x + 1, "'#' or '.'")),
+            }
+        }
+        if line.text.chars().count() < width {
+            return Err(line.error_at_end(format!("a row of {} columns", width)));
+        }
+    }
+
+    let is_lock = first.text.chars().all(|c| c == '#');
+    if !is_lock && !last.text.chars().all(|c| c == '#') {
+        return Err(ParseError::new(25, first.number, 1, "a lock with a filled top row or a key with a filled bottom row"));
+    }
+
+    Ok((is_lock, heights.into_iter().map(|h| h - 1).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        #####
+        .####
+        .####
+        .####
+        .#.#.
+        .#...
+        .....
+
+        #####
+        ##.##
+        .#.##
+        ...##
+        ...#.
+        ...#.
+        .....
+
+        .....
+        #....
+        #....
+        #...#
+        #.#.#
+        #.###
+        #####
+
+        .....
+        .....
+        #.#..
+        ###..
+        ###.#
+        ###.#
+        #####
+
+        .....
+        .....
+        .....
+        #....
+        #.#..
+        #.#.#
+        #####
+    "};
+
+    #[test]
+    fn it_parses_locks_and_keys_into_heights() {
+        let schematics = parse_schematics(EXAMPLE).unwrap();
+
+        assert_eq!(vec![vec![0, 5, 3, 4, 3], vec![1, 2, 0, 5, 3]], schematics.locks);
+        assert_eq!(vec![vec![5, 0, 2, 1, 3], vec![4, 3, 4, 0, 2], vec![3, 0, 2, 0, 1]], schematics.keys);
+        assert_eq!(5, schematics.space);
+    }
+
+    #[test]
+    fn it_counts_fitting_pairs() {
+        assert_eq!(Ok(3), count_fitting_pairs(EXAMPLE));
+    }
+
+    #[test]
+    fn it_reports_invalid_schematics() {
+        assert_eq!(Err(ParseError::new(25, 2, 2, "'#' or '.'")), parse_schematics("###\n.x.\n..."));
+        assert_eq!(Err(ParseError::new(25, 2, 3, "a row of 3 columns")), parse_schematics("###\n..\n..."));
+        assert_eq!(
+            Err(ParseError::new(25, 1, 1, "a lock with a filled top row or a key with a filled bottom row")),
+            parse_schematics("#.#\n...\n...")
+        );
+        assert_eq!(
+            Err(ParseError::new(25, 8, 1, "a schematic of 3 rows")),
+            parse_schematics("###\n...\n...\n\n...\n...\n...\n###")
+        );
+    }
+
+    #[test]
+    fn it_checks_every_schematic_against_the_first_one() {
+        assert_eq!(
+            Err(ParseError::new(25, 6, 1, "a schematic of 2 rows")),
+            parse_schematics("###\n...\n\n...\n...\n###")
+        );
+        assert_eq!(
+            Err(ParseError::new(25, 6, 1, "a schematic of 3 rows")),
+            parse_schematics("###\n...\n...\n\n...\n###")
+        );
+        assert_eq!(
+            Err(ParseError::new(25, 5, 4, "a row of 3 columns")),
+            parse_schematics("###\n...\n...\n\n####\n....\n....")
+        );
+        assert_eq!(
+            Err(ParseError::new(25, 5, 3, "a row of 3 columns")),
+            parse_schematics("###\n...\n...\n\n##\n..\n..")
+        );
+        assert_eq!(Ok(0), parse_schematics("###\n...\n\n...\n###").map(|schematics| schematics.space));
+    }
+}
@@ -1,7 +1,61 @@
+use crate::input_reader::read_lines;
+use crate::parse_error::ParseError;
+use crate::solver::{Answer, Solver};
+use regex::Regex;
+
+pub struct Day3;
+
+impl Solver for Day3 {
+    type Input = Vec<String>;
+
+    // corrupted memory is any text, only the well-formed instructions matter
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        Ok(read_lines(input))
+    }
+
+    fn part1(&self, lines: &Self::Input) -> Answer {
+        sum_lines(lines).into()
+    }
+
+    fn part2(&self, lines: &Self::Input) -> Answer {
+        sum_only_enabled(&lines.join("")).into()
+    }
+}
+
+pub fn sum_all_lines(input: &str) -> usize {
+    sum_lines(&read_lines(input))
+}
+
+fn sum_lines(lines: &[String]) -> usize {
+    lines.iter().map(|l| mul_sum_line(l))
+        .sum()
+}
+
+pub fn sum_only_enabled_lines(input: &str) -> usize {
+    sum_only_enabled(&read_lines(input).join(""))
+}
+
+fn sum_only_enabled(line: &str) -> usize {
+    let enabled = Regex::new(r"don't\(\)(.*?do\(\)|.*)").unwrap()
+        .replace_all(line, "");
+
+    mul_sum_line(&enabled)
+}
+
+fn mul_sum_line(line: &str) -> usize {
+    Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap()
+        .captures_iter(line)
+        .map(|c| c.extract())
+        .map(|(_, [first, second])| {
+            first.parse::<usize>().unwrap() * second.parse::<usize>().unwrap()
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
-    use regex::Regex;
-    use crate::input_reader::{read_input_file, read_lines};
+    use super::*;
+    use crate::personal_input;
 
     #[test]
     fn it_calculates_the_sum_of_multiplications() {
@@ -11,10 +65,7 @@ mod tests {
 
     #[test]
     fn it_solve_first_puzzle() {
-        let total: usize = read_lines(&read_input_file("input_03"))
-            .iter().map(|l| mul_sum_line(l))
-            .sum();
-        assert_eq!(187194524, total)
+        assert_eq!(187194524, sum_all_lines(&personal_input!("input_03")))
     }
 
     #[test]
@@ -25,24 +76,6 @@ mod tests {
 
     #[test]
     fn it_solve_second_puzzle() {
-        let line = read_lines(&read_input_file("input_03")).join("");
-        assert_eq!(127092535, sum_only_enabled(&line))
-    }
-
-    fn sum_only_enabled(line: &str) -> usize {
-        let enabled = Regex::new(r"don't\(\)(.*?do\(\)|.*)").unwrap()
-            .replace_all(line, "");
-
-        mul_sum_line(&enabled)
-    }
-
-    fn mul_sum_line(line: &str) -> usize {
-        Regex::new(r"mul\((\d+),(\d+)\)").unwrap()
-            .captures_iter(line)
-            .map(|c| c.extract())
-            .map(|(_, [first, second])| {
-                first.parse::<usize>().unwrap() * second.parse::<usize>().unwrap()
-            })
-            .sum()
+        assert_eq!(127092535, sum_only_enabled_lines(&personal_input!("input_03")))
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,91 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse_error::ParseError;
+use crate::solver::{Answer, Solver};
+
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Input = Grid<char>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(&self, parsed_input: &Self::Input) -> Answer {
+        xmas_occurrences(parsed_input).into()
+    }
+
+    fn part2(&self, parsed_input: &Self::Input) -> Answer {
+        x_mas_occurrences(parsed_input).into()
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(4, input, "a letter", Some)
+}
+
+pub fn count_x_mas(input: &str) -> Result<usize, ParseError> {
+    Ok(x_mas_occurrences(&parse_input(input)?))
+}
+
+fn x_mas_occurrences(grid: &Grid<char>) -> usize {
+    grid.find_all(&'A')
+        .filter(|&s| filter_x_mas(grid, s))
+        .count()
+}
+
+fn filter_x_mas(grid: &Grid<char>, start: Point) -> bool {
+    let words = [
+        vec![(-1, -1), (0, 0), (1, 1)],
+        vec![(-1, 1), (0, 0), (1, -1)],
+    ].iter()
+        .map(|d| navigate_to_direction(grid, start, d))
+        .collect::<Vec<_>>();
+
+    if words.len() != 2 {
+        return false
+    }
+
+    words.iter().all(|w| w == "MAS" || w == "SAM")
+}
+
+pub fn count_xmas(input: &str) -> Result<usize, ParseError> {
+    Ok(xmas_occurrences(&parse_input(input)?))
+}
+
+fn xmas_occurrences(grid: &Grid<char>) -> usize {
+    grid.find_all(&'X')
+        .flat_map(|s| navigate_xmas(grid, s))
+        .filter(|w| w == "XMAS")
+        .count()
+}
+
+fn navigate_xmas(grid: &Grid<char>, start: Point) -> Vec<String> {
+    [
+        vec![(0, 0), (0, 1), (0, 2), (0, 3)],
+        vec![(0, 0), (0, -1), (0, -2), (0, -3)],
+        vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+        vec![(0, 0), (-1, 0), (-2, 0), (-3, 0)],
+        vec![(0, 0), (1, 1), (2, 2), (3, 3)],
+        vec![(0, 0), (-1, -1), (-2, -2), (-3, -3)],
+        vec![(0, 0), (1, -1), (2, -2), (3, -3)],
+        vec![(0, 0), (-1, 1), (-2, 2), (-3, 3)],
+    ].iter()
+        .map(|d| navigate_to_direction(grid, start, d))
+        .collect()
+}
+
+fn navigate_to_direction(grid: &Grid<char>, start: Point, direction: &[(isize, isize)]) -> String {
+    direction.iter().filter_map(|&offset| grid.get(start + Point::from(offset))).collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
 
-    use crate::input_reader::{read_input_file, read_lines};
-
     #[test]
     fn it_count_xmas() {
         let input = indoc! {"
@@ -18,14 +100,19 @@ mod tests {
         MAMMMXMMMM
         MXMXAXMASX"};
 
-        assert_eq!(18, count_xmas(input))
+        assert_eq!(Ok(18), count_xmas(input))
+    }
+
+    #[test]
+    fn it_rejects_rows_of_different_length() {
+        assert_eq!(Err(ParseError::new(4, 2, 4, "a row of 4 cells")), parse_input("XMAS\nXMA"));
     }
 
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_04");
+        let input = &personal_input!("input_04");
 
-        assert_eq!(2718, count_xmas(input))
+        assert_eq!(Ok(2718), count_xmas(input))
     }
 
     #[test]
@@ -42,94 +129,13 @@ mod tests {
         M.M.M.M.M.
         .........."};
 
-        assert_eq!(9, count_x_mas(input))
+        assert_eq!(Ok(9), count_x_mas(input))
     }
 
     #[test]
     fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_04");
-
-        assert_eq!(2046, count_x_mas(input))
-    }
-
-    fn count_x_mas(input: &str) -> usize {
-        let parsed_input: Vec<_> = read_lines(input).iter()
-            .map(|l| l.chars().collect::<Vec<_>>())
-            .collect();
-
-        parsed_input
-            .clone()
-            .into_iter()
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.iter().enumerate().filter_map(|(x, &char)| {
-                    if char == 'A' {
-                        Some((x as isize, y as isize))
-                    } else {
-                        None
-                    }
-                }).collect::<Vec<_>>()
-            })
-            .filter(|&s| filter_x_mas(&parsed_input, s))
-            .count()
-    }
-
-    fn filter_x_mas(parsed_input: &Vec<Vec<char>>, start: (isize, isize)) -> bool {
-        let words = [
-            vec![(-1, -1), (0, 0), (1, 1)],
-            vec![(-1, 1), (0, 0), (1, -1)],
-        ].iter()
-            .map(|d| navigate_to_direction(&parsed_input, start, d))
-            .collect::<Vec<_>>();
-
-        if words.len() != 2 {
-            return false
-        }
-
-        words.iter().all(|w| w == "MAS" || w == "SAM")
-    }
-
-    fn count_xmas(input: &str) -> usize {
-        let parsed_input: Vec<_> = read_lines(input).iter()
-            .map(|l| l.chars().collect::<Vec<_>>())
-            .collect();
-
-        parsed_input
-            .clone()
-            .into_iter()
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.iter().enumerate().filter_map(|(x, &char)| {
-                    if char == 'X' {
-                        Some((x as isize, y as isize))
-                    } else {
-                        None
-                    }
-                }).collect::<Vec<_>>()
-            })
-            .flat_map(|s| navigate_xmas(&parsed_input, s))
-            .filter(|w| w == "XMAS")
-            .count()
-    }
-
-    fn navigate_xmas(parsed_input: &Vec<Vec<char>>, start: (isize, isize)) -> Vec<String> {
-        [
-            vec![(0, 0), (0, 1), (0, 2), (0, 3)],
-            vec![(0, 0), (0, -1), (0, -2), (0, -3)],
-            vec![(0, 0), (1, 0), (2, 0), (3, 0)],
-            vec![(0, 0), (-1, 0), (-2, 0), (-3, 0)],
-            vec![(0, 0), (1, 1), (2, 2), (3, 3)],
-            vec![(0, 0), (-1, -1), (-2, -2), (-3, -3)],
-            vec![(0, 0), (1, -1), (2, -2), (3, -3)],
-            vec![(0, 0), (-1, 1), (-2, 2), (-3, 3)],
-        ].iter()
-            .map(|d| navigate_to_direction(&parsed_input, start, d))
-            .collect()
-    }
+        let input = &personal_input!("input_04");
 
-    fn navigate_to_direction(map: &Vec<Vec<char>>, (x, y): (isize, isize), direction: &Vec<(isize, isize)>) -> String {
-        direction.iter().filter_map(|(dx, dy)| {
-            map.get((y + *dy) as usize)?.get((x + *dx) as usize)
-        }).collect()
+        assert_eq!(Ok(2046), count_x_mas(input))
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,112 @@
+use crate::parse_error::{lines, ParseError};
+use crate::solver::{Answer, Solver};
+use std::collections::HashMap;
+
+pub struct Day5;
+
+impl Solver for Day5 {
+    type Input = (Preconditions, Vec<Vec<usize>>);
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(&self, (preconditions, updates): &Self::Input) -> Answer {
+        sum_middle_page_number_for_correct_updates(preconditions, updates.clone()).into()
+    }
+
+    fn part2(&self, (preconditions, updates): &Self::Input) -> Answer {
+        sum_middle_page_number_for_corrected_updates(preconditions, updates.clone()).into()
+    }
+}
+
+pub fn sum_middle_page_number_for_corrected_updates(
+    preconditions: &HashMap<usize, Vec<usize>>,
+    updates: Vec<Vec<usize>>,
+) -> usize {
+    updates.iter()
+        .filter(|u| !is_update_valid(preconditions, u))
+        .map(|u| fix_errors(preconditions, u.clone()))
+        .map(|u| u[u.len() / 2])
+        .sum()
+}
+
+fn fix_errors(
+    preconditions: &HashMap<usize, Vec<usize>>,
+    mut update: Vec<usize>,
+) -> Vec<usize> {
+    while let Some((first, second)) = finds_first_error_in_update(preconditions, &update) {
+        update.swap(first, second);
+    }
+
+    update
+}
+
+pub fn sum_middle_page_number_for_correct_updates(
+    preconditions: &HashMap<usize, Vec<usize>>,
+    updates: Vec<Vec<usize>>,
+) -> usize {
+    updates.iter()
+        .filter(|u| is_update_valid(preconditions, u))
+        .map(|u| u[u.len() / 2])
+        .sum()
+}
+
+fn is_update_valid(
+    preconditions: &HashMap<usize, Vec<usize>>,
+    update: &[usize],
+) -> bool {
+    finds_first_error_in_update(preconditions, update).is_none()
+}
+
+fn finds_first_error_in_update(
+    preconditions: &HashMap<usize, Vec<usize>>,
+    update: &[usize],
+) -> Option<(usize, usize)> {
+    update.iter()
+        .enumerate()
+        .find_map(|(index, value)| {
+            let p = preconditions.get(value)?;
+            let second = (index + 1..update.len())
+                .find(|&j| p.contains(&update[j]))?;
+            Some((index, second))
+        })
+}
+
+pub type Preconditions = HashMap<usize, Vec<usize>>;
+
+pub fn parse_input(input: &str) -> Result<(Preconditions, Vec<Vec<usize>>), ParseError> {
+    let mut preconditions = HashMap::new();
+    let mut lines = lines(5, input);
+
+    for line in lines.by_ref() {
+        if line.text.is_empty() {
+            break;
+        }
+
+        let (left, right) = line.split_once("|")?;
+        let left = line.parse::<usize>(left, "a page number")?;
+        let right = line.parse::<usize>(right, "a page number")?;
+        preconditions.entry(right)
+            .and_modify(|pre: &mut Vec<_>| pre.push(left))
+            .or_insert(vec![left]);
+    }
+
+    let updates = lines
+        .map(|line| {
+            line.text.split(",")
+                .map(|v| line.parse::<usize>(v, "a page number"))
+                .collect()
+        }).collect::<Result<_, _>>()?;
+
+    Ok((preconditions, updates))
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use super::*;
+    use crate::personal_input;
     use indoc::indoc;
-    use itertools::Itertools;
-    use crate::input_reader::{read_input_file, read_lines};
 
     #[test]
     fn it_sums_middle_page_number_for_correct_updates() {
@@ -37,7 +140,7 @@ mod tests {
         61,13,29
         97,13,75,29,47"};
 
-        let (preconditions, updates) = parse_input(input);
+        let (preconditions, updates) = parse_input(input).unwrap();
 
         assert_eq!(
             143,
@@ -45,11 +148,17 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_reports_invalid_rules_and_updates() {
+        assert_eq!(Err(ParseError::new(5, 2, 6, "'|'")), parse_input("47|53\n97-13\n\n75,47"));
+        assert_eq!(Err(ParseError::new(5, 4, 4, "a page number")), parse_input("47|53\n97|13\n\n75,,47"));
+    }
+
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_05");
+        let input = &personal_input!("input_05");
 
-        let (preconditions, updates) = parse_input(input);
+        let (preconditions, updates) = parse_input(input).unwrap();
 
         assert_eq!(
             5639,
@@ -89,97 +198,17 @@ mod tests {
         61,13,29
         97,13,75,29,47"};
 
-        let (preconditions, updates) = parse_input(input);
+        let (preconditions, updates) = parse_input(input).unwrap();
 
         assert_eq!(123, sum_middle_page_number_for_corrected_updates(&preconditions, updates));
     }
 
     #[test]
     fn it_solves_second_part() {
-        let input = &read_input_file("input_05");
+        let input = &personal_input!("input_05");
 
-        let (preconditions, updates) = parse_input(input);
+        let (preconditions, updates) = parse_input(input).unwrap();
 
         assert_eq!(5273, sum_middle_page_number_for_corrected_updates(&preconditions, updates));
     }
-
-    fn sum_middle_page_number_for_corrected_updates(
-        preconditions: &HashMap<usize, Vec<usize>>,
-        updates: Vec<Vec<usize>>,
-    ) -> usize {
-        updates.iter()
-            .filter(|u| !is_update_valid(&preconditions, u))
-            .map(|u| fix_errors(&preconditions, u.clone()))
-            .map(|u| u[u.len() / 2])
-            .sum()
-    }
-
-    fn fix_errors(
-        preconditions: &HashMap<usize, Vec<usize>>,
-        mut update: Vec<usize>,
-    ) -> Vec<usize> {
-        while let Some((first, second)) = finds_first_error_in_update(preconditions, &update) {
-            update.swap(first, second);
-        }
-
-        return update;
-    }
-
-    fn sum_middle_page_number_for_correct_updates(
-        preconditions: &HashMap<usize, Vec<usize>>,
-        updates: Vec<Vec<usize>>,
-    ) -> usize {
-        updates.iter()
-            .filter(|u| is_update_valid(&preconditions, u))
-            .map(|u| u[u.len() / 2])
-            .sum()
-    }
-
-    fn is_update_valid(
-        preconditions: &HashMap<usize, Vec<usize>>,
-        update: &Vec<usize>,
-    ) -> bool {
-        finds_first_error_in_update(preconditions, update).is_none()
-    }
-
-    fn finds_first_error_in_update(
-        preconditions: &HashMap<usize, Vec<usize>>,
-        update: &Vec<usize>,
-    ) -> Option<(usize, usize)> {
-        update.iter()
-            .enumerate()
-            .find_map(|(index, value)| {
-                let p = preconditions.get(value)?;
-                let second = (index + 1..update.len())
-                    .find(|&j| p.contains(&update[j]))?;
-                Some((index, second))
-            })
-    }
-
-    fn parse_input(input: &str) -> (HashMap<usize, Vec<usize>>, Vec<Vec<usize>>) {
-        let (raw_rules, raw_updates): (Vec<_>, Vec<_>) = read_lines(input).into_iter()
-            .partition(|l| l.contains("|"));
-
-        let preconditions = raw_rules.iter()
-            .fold(HashMap::new(), |mut acc, rr| {
-                let (left, right): (usize, usize) = rr.split("|")
-                    .map(|v| v.parse::<usize>().unwrap())
-                    .collect_tuple()
-                    .unwrap();
-                acc.entry(right)
-                    .and_modify(|pre: &mut Vec<_>| pre.push(left))
-                    .or_insert(vec![left]);
-                acc
-            });
-
-        let updates = raw_updates.iter()
-            .skip(1)
-            .map(|ru| {
-                ru.split(",")
-                    .map(|v| v.parse::<usize>().unwrap())
-                    .collect()
-            }).collect();
-
-        (preconditions, updates)
-    }
-}
\ No newline at end of file
+}
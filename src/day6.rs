@@ -1,130 +1,112 @@
-#[cfg(test)]
-mod tests {
-    use crate::input_reader::{read_input_file, read_lines};
-    use indoc::indoc;
-    use std::collections::HashSet;
-
-    #[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
-    enum Direction {
-        North,
-        East,
-        South,
-        West,
-    }
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::parse_error::{empty_input, ParseError};
+use crate::solver::{Answer, Solver};
+use std::collections::HashSet;
 
-    impl Direction {
-        fn step(&self, position: &(isize, isize)) -> (isize, isize) {
-            match self {
-                Direction::North => (position.0, position.1 - 1),
-                Direction::East => (position.0 + 1, position.1),
-                Direction::South => (position.0, position.1 + 1),
-                Direction::West => (position.0 - 1, position.1)
-            }
-        }
+pub struct Day6;
 
-        fn rotate(&self) -> Self {
-            match self {
-                Direction::North => Direction::East,
-                Direction::East => Direction::South,
-                Direction::South => Direction::West,
-                Direction::West => Direction::North
-            }
-        }
+impl Solver for Day6 {
+    type Input = Map;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        Map::from(input)
     }
 
-    #[derive(Debug)]
-    struct Map {
-        guard: (isize, isize),
-        obstacles: HashSet<(isize, isize)>,
-        dimensions: (usize, usize),
+    fn part1(&self, map: &Self::Input) -> Answer {
+        map.walk().len().into()
     }
 
-    impl Map {
-        fn from(input: &str) -> Self {
-            let map = read_lines(input)
-                .iter()
-                .map(|l| l.chars().collect::<Vec<_>>())
-                .collect::<Vec<_>>();
-
-            let dimensions = (map[0].len(), map.len());
-            let mut guard = None;
-            let mut obstacles = HashSet::new();
-
-            for (y, line) in map.iter().enumerate() {
-                for (x, &char) in line.iter().enumerate() {
-                    match char {
-                        '^' => guard = Some((x as isize, y as isize)),
-                        '#' => { obstacles.insert((x as isize, y as isize)); }
-                        _ => ()
-                    }
-                }
-            }
+    fn part2(&self, map: &Self::Input) -> Answer {
+        map.count_obfuscations().into()
+    }
+}
 
-            Self { guard: guard.unwrap(), obstacles, dimensions }
-        }
+#[derive(Debug)]
+pub struct Map {
+    guard: Point,
+    obstacles: Grid<bool>,
+}
 
-        fn walk(&self) -> HashSet<(isize, isize)> {
-            let mut current_position = self.guard;
-            let mut direction = Direction::North;
-            let mut visited = HashSet::new();
+impl Map {
+    pub fn from(input: &str) -> Result<Self, ParseError> {
+        let grid = Grid::parse(6, input, "'.', '#' or '^'", |c| matches!(c, '.' | '#' | '^').then_some(c))?;
 
-            while self.is_in_boundaries(&mut current_position) {
-                visited.insert(current_position);
+        let guard = grid.find(&'^').ok_or_else(|| empty_input(6, "a guard '^'"))?;
+        Ok(Self { guard, obstacles: grid.map(|&c| c == '#') })
+    }
+
+    pub fn walk(&self) -> HashSet<Point> {
+        let mut current_position = self.guard;
+        let mut direction = Direction::Up;
+        let mut visited = HashSet::new();
 
-                let new_position = direction.step(&current_position);
+        while self.is_in_boundaries(&current_position) {
+            visited.insert(current_position);
 
-                if self.obstacles.contains(&new_position) {
-                    direction = direction.rotate();
-                    continue;
-                }
+            let new_position = current_position.step(direction);
 
-                current_position = new_position
+            if self.is_obstacle(new_position) {
+                direction = direction.rotate_right();
+                continue;
             }
 
-            visited
+            current_position = new_position
         }
 
-        fn is_in_boundaries(&self, position: &(isize, isize)) -> bool {
-            position.0 >= 0 && position.0 < (self.dimensions.0 as isize)
-                && position.1 >= 0 && position.1 < (self.dimensions.1 as isize)
-        }
+        visited
+    }
 
-        fn count_obfuscations(&self) -> usize {
-            self.walk()
-                .iter()
-                .filter(|&&w| self.is_looping(w))
-                .count()
-        }
+    fn is_in_boundaries(&self, position: &Point) -> bool {
+        self.obstacles.contains(*position)
+    }
 
-        fn is_looping(&self, new_wall: (isize, isize)) -> bool {
-            if new_wall == self.guard {
-                return false
-            }
+    fn is_obstacle(&self, position: Point) -> bool {
+        self.obstacles.get(position) == Some(&true)
+    }
 
-            let mut loops: HashSet<((isize, isize), Direction)> = HashSet::new();
-            let mut current_position = self.guard;
-            let mut direction = Direction::North;
+    pub fn count_obfuscations(&self) -> usize {
+        self.walk()
+            .iter()
+            .filter(|&&w| self.is_looping(w))
+            .count()
+    }
+
+    fn is_looping(&self, new_wall: Point) -> bool {
+        if new_wall == self.guard {
+            return false
+        }
 
-            while self.is_in_boundaries(&current_position) {
-                if loops.contains(&(current_position, direction)) {
-                    return true
-                }
+        let mut loops: HashSet<(Point, Direction)> = HashSet::new();
+        let mut current_position = self.guard;
+        let mut direction = Direction::Up;
 
-                loops.insert((current_position, direction));
+        while self.is_in_boundaries(&current_position) {
+            if loops.contains(&(current_position, direction)) {
+                return true
+            }
 
-                let new_position = direction.step(&current_position);
+            loops.insert((current_position, direction));
 
-                if self.obstacles.contains(&new_position) || new_position == new_wall {
-                    direction = direction.rotate();
-                    continue;
-                }
+            let new_position = current_position.step(direction);
 
-                current_position = new_position
+            if self.is_obstacle(new_position) || new_position == new_wall {
+                direction = direction.rotate_right();
+                continue;
             }
 
-            false
+            current_position = new_position
         }
+
+        false
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personal_input;
+    use indoc::indoc;
 
     #[test]
     fn it_calculates_the_steps() {
@@ -140,16 +122,22 @@ mod tests {
         #.........
         ......#..."};
 
-        let map = Map::from(input);
+        let map = Map::from(input).unwrap();
 
         assert_eq!(6, map.count_obfuscations());
     }
 
+    #[test]
+    fn it_reports_unknown_tiles_and_missing_guard() {
+        assert_eq!(Some(ParseError::new(6, 2, 3, "'.', '#' or '^'")), Map::from("..#\n.^x").err());
+        assert_eq!(Some(ParseError::new(6, 1, 1, "a guard '^'")), Map::from("..#\n...").err());
+    }
+
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_06");
+        let input = &personal_input!("input_06");
 
-        let map = Map::from(input);
+        let map = Map::from(input).unwrap();
 
         assert_eq!(5444, map.walk().len())
     }
@@ -168,7 +156,7 @@ mod tests {
         #.........
         ......#..."};
 
-        let map = Map::from(input);
+        let map = Map::from(input).unwrap();
 
         assert_eq!(6, map.count_obfuscations());
     }
@@ -176,9 +164,9 @@ mod tests {
     // Really really slow ~ 24s
     #[test]
     fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_06");
+        let input = &personal_input!("input_06");
 
-        let map = Map::from(input);
+        let map = Map::from(input).unwrap();
 
         assert_eq!(1946, map.count_obfuscations());
     }
@@ -1,51 +1,100 @@
-#[cfg(test)]
-mod tests {
-    use indoc::indoc;
-    use crate::input_reader::{read_input_file, read_lines};
+use crate::parse_error::{lines, Line, ParseError};
+use crate::solver::{Answer, Solver};
 
+pub struct Day7;
 
-    type Combiner = fn(usize, usize) -> usize;
+impl Solver for Day7 {
+    type Input = Vec<Equation>;
 
-    fn sum(a: usize, b: usize) -> usize {
-        a + b
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
     }
 
-    fn mul(a: usize, b: usize) -> usize {
-        a * b
+    fn part1(&self, equations: &Self::Input) -> Answer {
+        sum_valid_equations(equations, &[sum, mul]).into()
     }
 
-    fn join(a: usize, b: usize) -> usize {
-        format!("{}{}", a.to_string(), b.to_string()).parse::<usize>().unwrap()
+    fn part2(&self, equations: &Self::Input) -> Answer {
+        sum_valid_equations(equations, &[sum, mul, join]).into()
     }
+}
 
-    struct Equation(usize, Vec<usize>);
+pub type Combiner = fn(usize, usize) -> usize;
 
-    impl Equation {
-        fn is_valid(&self, combiners: &Vec<Combiner>) -> bool {
-            let current = self.1[0];
-            self.is_valid_rec(current, 1, combiners)
-        }
+pub fn sum(a: usize, b: usize) -> usize {
+    a + b
+}
+
+pub fn mul(a: usize, b: usize) -> usize {
+    a * b
+}
 
-        fn is_valid_rec(&self, current: usize, next_index: usize, combiners: &Vec<Combiner>) -> bool {
-            if self.1.len() == next_index && current == self.0 {
-                return true;
-            }
+pub fn join(a: usize, b: usize) -> usize {
+    format!("{}{}", a, b).parse::<usize>().unwrap()
+}
 
-            if self.1.len() == next_index {
-                return false;
-            }
+#[derive(Debug, PartialEq)]
+pub struct Equation(pub usize, pub Vec<usize>);
 
-            combiners.iter()
-                .any(|c|
-                    self.is_valid_rec(c(current, self.1[next_index]), next_index + 1, combiners)
-                )
+impl Equation {
+    fn is_valid(&self, combiners: &[Combiner]) -> bool {
+        let current = self.1[0];
+        self.is_valid_rec(current, 1, combiners)
+    }
+
+    fn is_valid_rec(&self, current: usize, next_index: usize, combiners: &[Combiner]) -> bool {
+        if self.1.len() == next_index && current == self.0 {
+            return true;
         }
+
+        if self.1.len() == next_index {
+            return false;
+        }
+
+        combiners.iter()
+            .any(|c|
+                self.is_valid_rec(c(current, self.1[next_index]), next_index + 1, combiners)
+            )
     }
+}
+
+pub fn total_calibration_result(input: &str, combiners: &[Combiner]) -> Result<usize, ParseError> {
+    Ok(sum_valid_equations(&parse_input(input)?, combiners))
+}
+
+fn sum_valid_equations(equations: &[Equation], combiners: &[Combiner]) -> usize {
+    equations
+        .iter()
+        .filter(|e| e.is_valid(combiners))
+        .map(|s| s.0)
+        .sum::<usize>()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Equation>, ParseError> {
+    lines(7, input)
+        .map(|line| parse_equation(&line))
+        .collect()
+}
+
+fn parse_equation(line: &Line) -> Result<Equation, ParseError> {
+    let (result, factors) = line.split_once(": ")?;
+    let result = line.parse::<usize>(result, "a test value")?;
+    let factors = factors.split(" ")
+        .map(|v| line.parse::<usize>(v, "a number"))
+        .collect::<Result<_, _>>()?;
+    Ok(Equation(result, factors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personal_input;
+    use indoc::indoc;
 
     #[test]
     fn it_validates_an_equation() {
         let equation = Equation(3267, vec![81, 40, 27]);
-        assert!(equation.is_valid(&vec![sum, mul]))
+        assert!(equation.is_valid(&[sum, mul]))
     }
 
     #[test]
@@ -61,36 +110,26 @@ mod tests {
             21037: 9 7 18 13
             292: 11 6 16 20"};
 
-        assert_eq!(3749, total_calibration_result(input, &vec![sum, mul]))
+        assert_eq!(Ok(3749), total_calibration_result(input, &[sum, mul]))
+    }
+
+    #[test]
+    fn it_reports_invalid_equations() {
+        assert_eq!(Err(ParseError::new(7, 1, 1, "a test value")), parse_input("x: 10 19"));
+        assert_eq!(Err(ParseError::new(7, 2, 10, "a number")), parse_input("190: 10 19\n3267: 81  27"));
     }
 
     #[test]
     fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_07");
+        let input = &personal_input!("input_07");
 
-        assert_eq!(4122618559853, total_calibration_result(input, &vec![sum, mul]))
+        assert_eq!(Ok(4122618559853), total_calibration_result(input, &[sum, mul]))
     }
 
     #[test]
     fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_07");
-
-        assert_eq!(227615740238334, total_calibration_result(input, &vec![sum, mul, join]))
-    }
+        let input = &personal_input!("input_07");
 
-    fn total_calibration_result(input: &str, combiners: &Vec<Combiner>) -> usize {
-        read_lines(input)
-            .iter()
-            .map(|l| {
-                let s = l.split(": ").collect::<Vec<_>>();
-                let result = s[0].parse::<usize>().unwrap();
-                let factors = s[1].split(" ")
-                    .map(|v| v.parse::<usize>().unwrap())
-                    .collect();
-                Equation(result, factors)
-            })
-            .filter(|e| e.is_valid(combiners))
-            .map(|s| s.0)
-            .sum::<usize>()
+        assert_eq!(Ok(227615740238334), total_calibration_result(input, &[sum, mul, join]))
     }
-}
\ No newline at end of file
+}
@@ -1,38 +1,55 @@
-use crate::input_reader::read_lines;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse_error::ParseError;
+use crate::solver::{Answer, Solver};
 use std::collections::{HashMap, HashSet};
 
-struct Map {
-    map: HashMap<char, Vec<(isize, isize)>>,
-    dimension: isize,
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Input = Map;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        Map::build_from(input)
+    }
+
+    fn part1(&self, map: &Self::Input) -> Answer {
+        map.all_antinodes(antinodes).len().into()
+    }
+
+    fn part2(&self, map: &Self::Input) -> Answer {
+        map.all_antinodes(antinodes_harmonics).len().into()
+    }
+}
+
+pub struct Map {
+    map: HashMap<char, Vec<Point>>,
+    grid: Grid<char>,
 }
 
 impl Map {
-    fn build_from(raw_input: &str) -> Self {
-        let mut map = HashMap::new();
+    pub fn build_from(raw_input: &str) -> Result<Self, ParseError> {
+        let grid = Grid::parse(8, raw_input, "'.' or an antenna frequency", |c| {
+            (c == '.' || c.is_ascii_alphanumeric()).then_some(c)
+        })?;
 
-        let input = read_lines(raw_input);
-        for (y, line) in input.iter().enumerate() {
-            for (x, char) in line.chars().enumerate() {
-                if char == '.' {
-                    continue;
-                }
-                let position = (x as isize, y as isize);
-                map.entry(char)
-                    .and_modify(|positions: &mut Vec<_>| positions.push(position))
-                    .or_insert(vec![position]);
-            }
+        let mut map = HashMap::new();
+        for (position, &char) in grid.iter().filter(|(_, &c)| c != '.') {
+            map.entry(char)
+                .and_modify(|positions: &mut Vec<_>| positions.push(position))
+                .or_insert(vec![position]);
         }
 
-        Self { map, dimension: input.len() as isize }
+        Ok(Self { map, grid })
     }
 
-    fn all_antinodes(&self, antinode_extender: AntinodeExtender) -> HashSet<Position> {
+    pub fn all_antinodes(&self, antinode_extender: AntinodeExtender) -> HashSet<Point> {
         let mut result = HashSet::new();
 
-        for (_, positions) in &self.map {
+        for positions in self.map.values() {
             for i in 0..(positions.len() - 1) {
                 for j in (i + 1)..positions.len() {
-                    result.extend(antinode_extender(positions[i], positions[j], self.dimension))
+                    result.extend(antinode_extender(positions[i], positions[j], &self.grid))
                 }
             }
         }
@@ -41,59 +58,46 @@ impl Map {
     }
 }
 
+pub type AntinodeExtender = fn(Point, Point, &Grid<char>) -> HashSet<Point>;
 
-type Dimension = isize;
-type AntinodeExtender = fn(Position, Position, Dimension) -> HashSet<Position>;
-type Position = (isize, isize);
+pub fn antinodes(p1: Point, p2: Point, grid: &Grid<char>) -> HashSet<Point> {
+    let delta = p1 - p2;
 
-fn antinodes(p1: Position, p2: Position, dimension: isize) -> HashSet<Position> {
-    let dx = p1.0 - p2.0;
-    let dy = p1.1 - p2.1;
-
-    let mut result = HashSet::new();
-
-    if is_position_in_boundaries((p1.0 + dx, p1.1 + dy), dimension) {
-        result.insert((p1.0 + dx, p1.1 + dy));
-    }
-
-    if is_position_in_boundaries((p2.0 - dx, p2.1 - dy), dimension) {
-        result.insert((p2.0 - dx, p2.1 - dy));
-    }
-
-    result
+    [p1 + delta, p2 - delta]
+        .into_iter()
+        .filter(|&p| grid.contains(p))
+        .collect()
 }
 
-fn antinodes_harmonics(p1: Position, p2: Position, dimension: isize) -> HashSet<Position> {
+pub fn antinodes_harmonics(p1: Point, p2: Point, grid: &Grid<char>) -> HashSet<Point> {
     let mut result = HashSet::new();
 
-    let dx = p1.0 - p2.0;
-    let dy = p1.1 - p2.1;
+    let delta = p1 - p2;
 
     let mut current = p1;
 
-    while is_position_in_boundaries(current, dimension) {
+    while grid.contains(current) {
         result.insert(current);
-        current = (current.0 + dx, current.1 + dy);
+        current += delta;
     }
 
     current = p2;
 
-    while is_position_in_boundaries(current, dimension) {
+    while grid.contains(current) {
         result.insert(current);
-        current = (current.0 - dx, current.1 - dy);
+        current -= delta;
     }
 
     result
 }
 
-fn is_position_in_boundaries(position: (isize, isize), dimension: isize) -> bool {
-    position.0 >= 0 && position.0 < dimension && position.1 >= 0 && position.1 < dimension
-}
-
 #[cfg(test)]
 mod tests {
     use crate::day8::{antinodes, antinodes_harmonics, Map};
-    use crate::input_reader::read_input_file;
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+    use crate::parse_error::ParseError;
+    use crate::personal_input;
     use indoc::indoc;
     use std::collections::HashSet;
 
@@ -114,21 +118,33 @@ mod tests {
         ............"};
 
 
-        assert_eq!(HashSet::from([(3, 1), (6, 7)]), antinodes((4, 3), (5, 5), 10));
-        assert_eq!(HashSet::from([(2, 6)]), antinodes((8, 4), (5, 5), 10));
+        let grid = Grid::new(10, 10, '.');
+        assert_eq!(
+            HashSet::from([Point::new(3, 1), Point::new(6, 7)]),
+            antinodes(Point::new(4, 3), Point::new(5, 5), &grid)
+        );
+        assert_eq!(HashSet::from([Point::new(2, 6)]), antinodes(Point::new(8, 4), Point::new(5, 5), &grid));
 
-        let map = Map::build_from(input);
+        let map = Map::build_from(input).unwrap();
 
-        assert_eq!(12, map.dimension);
+        assert_eq!((12, 12), (map.grid.width(), map.grid.height()));
         assert_eq!(14, map.all_antinodes(antinodes).len());
         assert_eq!(34, map.all_antinodes(antinodes_harmonics).len())
     }
 
+    #[test]
+    fn it_reports_invalid_map_tiles() {
+        assert_eq!(
+            Some(ParseError::new(8, 2, 3, "'.' or an antenna frequency")),
+            Map::build_from("..0.\n..#.").err()
+        );
+    }
+
     #[test]
     fn it_solves_both_puzzles() {
-        let input = &read_input_file("input_08");
+        let input = &personal_input!("input_08");
 
-        let map = Map::build_from(input);
+        let map = Map::build_from(input).unwrap();
         assert_eq!(344, map.all_antinodes(antinodes).len());
         assert_eq!(1182, map.all_antinodes(antinodes_harmonics).len())
     }
@@ -1,154 +1,198 @@
-#[cfg(test)]
-mod tests {
-    use crate::input_reader::read_input_file;
+use crate::parse_error::{empty_input, Line, ParseError};
+use crate::solver::{Answer, Solver};
 
-    #[test]
-    fn it_calculates_checksum() {
-        let input = "2333133121414131402";
-        let expanded_sequence = expand(input);
-        assert_eq!(
-            vec!["0", "0", ".", ".", ".", "1", "1", "1", ".", ".", ".", "2", ".", ".", ".", "3", "3", "3", ".", "4", "4", ".", "5", "5", "5", "5", ".", "6", "6", "6", "6", ".", "7", "7", "7", ".", "8", "8", "8", "8", "9", "9"],
-            expanded_sequence
-        );
+pub struct Day9;
 
-        let adjusted = adjust_sequence(expanded_sequence);
-        assert_eq!(
-            vec!["0", "0", "9", "9", "8", "1", "1", "1", "8", "8", "8", "2", "7", "7", "7", "3", "3", "3", "6", "4", "4", "6", "5", "5", "5", "5", "6", "6", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", "."],
-            adjusted);
-        assert_eq!(1928, calculate_checksum(adjusted));
-    }
+impl Solver for Day9 {
+    type Input = Vec<String>;
 
-    #[test]
-    fn it_solves_first_puzzle() {
-        let input = &read_input_file("input_09");
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        expand(input)
+    }
 
-        let expanded_sequence = expand(input);
-        let adjusted = adjust_sequence(expanded_sequence);
-        assert_eq!(6384282079460, calculate_checksum(adjusted));
+    fn part1(&self, sequence: &Self::Input) -> Answer {
+        calculate_checksum(adjust_sequence(sequence.clone())).into()
     }
 
-    #[test]
-    fn it_calculates_checksum_with_sequence_contiguous() {
-        let input = "2333133121414131402";
-        let expanded_sequence = expand(input);
-        let adjusted = adjust_sequence_contiguous(expanded_sequence);
-        assert_eq!(
-            vec!["0", "0", "9", "9", "2", "1", "1", "1", "7", "7", "7", ".", "4", "4", ".", "3", "3", "3", ".", ".", ".", ".", "5", "5", "5", "5", ".", "6", "6", "6", "6", ".", ".", ".", ".", ".", "8", "8", "8", "8", ".", "."],
-            adjusted);
-        assert_eq!(2858, calculate_checksum(adjusted));
+    fn part2(&self, sequence: &Self::Input) -> Answer {
+        calculate_checksum(adjust_sequence_contiguous(sequence.clone())).into()
     }
+}
 
-    #[test]
-    fn it_solves_second_puzzle() {
-        let input = &read_input_file("input_09");
+pub fn calculate_checksum(adjusted: Vec<String>) -> usize {
+    let mut result = 0;
 
-        let expanded_sequence = expand(input);
-        let adjusted = adjust_sequence_contiguous(expanded_sequence);
-        assert_eq!(6408966547049, calculate_checksum(adjusted));
+    for (index, value) in adjusted.iter().enumerate() {
+        if value == "." {
+            continue;
+        }
+
+        result += index * value.parse::<usize>().unwrap();
     }
 
-    fn calculate_checksum(adjusted: Vec<String>) -> usize {
-        let mut result = 0;
+    result
+}
 
-        for (index, value) in adjusted.iter().enumerate() {
-            if value == "." {
-                continue;
-            }
+pub fn adjust_sequence(sequence: Vec<String>) -> Vec<String> {
+    let mut adjusted = sequence.clone();
+    let mut left = 0;
+    let mut right = adjusted.len() - 1;
 
-            result += index * value.parse::<usize>().unwrap();
+    loop {
+        while adjusted[left] != "." && left < adjusted.len() {
+            left += 1;
         }
 
-        result
+        while adjusted[right] == "." && right > 0 {
+            right -= 1
+        }
+
+        if left > right {
+            break;
+        }
+
+        adjusted.swap(left, right);
     }
+    adjusted
+}
+
+pub fn adjust_sequence_contiguous(sequence: Vec<String>) -> Vec<String> {
+    let mut adjusted = sequence.clone();
+    let last = sequence.last().unwrap().parse::<usize>().unwrap();
+
+    let mut right = adjusted.len() - 1;
+    for current in (1..=last).rev() {
+        while adjusted[right] != current.to_string() {
+            right -= 1;
+        }
+
+        let mut len = 0;
+
+        while adjusted[right - len] == current.to_string() {
+            len += 1
+        }
 
-    fn adjust_sequence(sequence: Vec<String>) -> Vec<String> {
-        let mut adjusted = sequence.clone();
         let mut left = 0;
-        let mut right = adjusted.len() - 1;
 
-        loop {
-            while adjusted[left] != "." && left < adjusted.len() {
+        while left < right {
+            while adjusted[left] != "." {
                 left += 1;
             }
 
-            while adjusted[right] == "." && right > 0 {
-                right -= 1
+            if left > right {
+                break
             }
 
-            if left > right {
+            let mut spaces = 0;
+
+            while left + spaces < adjusted.len() && adjusted[left + spaces] == "." {
+                spaces += 1;
+            }
+
+            if spaces >= len {
+                for i in 0..len {
+                    adjusted.swap(left + i, right - i);
+                }
                 break;
             }
 
-            adjusted.swap(left, right);
+            left += spaces;
         }
-        adjusted
     }
 
-    fn adjust_sequence_contiguous(sequence: Vec<String>) -> Vec<String> {
-        let mut adjusted = sequence.clone();
-        let last = sequence.last().unwrap().parse::<usize>().unwrap();
-
-        let mut right = adjusted.len() - 1;
-        for current in (1..=last).rev() {
-            while adjusted[right] != current.to_string() {
-                right -= 1;
-            }
+    adjusted
+}
 
-            let mut len = 0;
+pub fn expand(input: &str) -> Result<Vec<String>, ParseError> {
+    let mut result = vec![];
 
-            while adjusted[right - len] == current.to_string() {
-                len += 1
-            }
+    let numbers = parse_disk_map(input)?;
+    let mut i = 0;
+    let mut current = 0;
+    while i < numbers.len() - 1 {
+        let times = numbers[i];
+        (0..times).for_each(|_| result.push(current.to_string()));
 
-            let mut left = 0;
+        let times = numbers[i + 1];
+        (0..times).for_each(|_| result.push(".".to_string()));
+        current += 1;
+        i += 2;
+    }
+    let times = numbers[i];
+    (0..times).for_each(|_| result.push(current.to_string()));
 
-            while left < right {
-                while adjusted[left] != "." {
-                    left += 1;
-                }
+    Ok(result)
+}
 
-                if left > right {
-                    break
-                }
+fn parse_disk_map(input: &str) -> Result<Vec<usize>, ParseError> {
+    let line = Line::new(9, 0, input.trim_end());
+    if line.text.is_empty() {
+        return Err(empty_input(9, "a disk map"));
+    }
 
-                let mut spaces = 0;
+    line.text.chars()
+        .enumerate()
+        .map(|(column, c)| {
+            c.to_digit(10)
+                .map(|d| d as usize)
+                .ok_or(ParseError::new(9, 1, column + 1, "a digit"))
+        })
+        .collect()
+}
 
-                while left + spaces < adjusted.len() && adjusted[left + spaces] == "." {
-                    spaces += 1;
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personal_input;
 
-                if spaces >= len {
-                    for i in 0..len {
-                        adjusted.swap(left + i, right - i);
-                    }
-                    break;
-                }
+    #[test]
+    fn it_calculates_checksum() {
+        let input = "2333133121414131402";
+        let expanded_sequence = expand(input).unwrap();
+        assert_eq!(
+            vec!["0", "0", ".", ".", ".", "1", "1", "1", ".", ".", ".", "2", ".", ".", ".", "3", "3", "3", ".", "4", "4", ".", "5", "5", "5", "5", ".", "6", "6", "6", "6", ".", "7", "7", "7", ".", "8", "8", "8", "8", "9", "9"],
+            expanded_sequence
+        );
 
-                left += spaces;
-            }
-        }
+        let adjusted = adjust_sequence(expanded_sequence);
+        assert_eq!(
+            vec!["0", "0", "9", "9", "8", "1", "1", "1", "8", "8", "8", "2", "7", "7", "7", "3", "3", "3", "6", "4", "4", "6", "5", "5", "5", "5", "6", "6", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", ".", "."],
+            adjusted);
+        assert_eq!(1928, calculate_checksum(adjusted));
+    }
 
-        adjusted
+    #[test]
+    fn it_reports_invalid_disk_maps() {
+        assert_eq!(Err(ParseError::new(9, 1, 4, "a digit")), expand("233x"));
+        assert_eq!(Err(ParseError::new(9, 1, 1, "a disk map")), expand("\n"));
     }
 
-    fn expand(input: &str) -> Vec<String> {
-        let mut result = vec![];
+    #[test]
+    fn it_solves_first_puzzle() {
+        let input = &personal_input!("input_09");
 
-        let numbers: Vec<_> = input.chars().collect();
-        let mut i = 0;
-        let mut current = 0;
-        while i < numbers.len() - 1 {
-            let times = numbers[i].to_digit(10).unwrap() as usize;
-            (0..times).for_each(|_| result.push(current.to_string()));
+        let expanded_sequence = expand(input).unwrap();
+        let adjusted = adjust_sequence(expanded_sequence);
+        assert_eq!(6384282079460, calculate_checksum(adjusted));
+    }
 
-            let times = numbers[i + 1].to_digit(10).unwrap() as usize;
-            (0..times).for_each(|_| result.push(".".to_string()));
-            current += 1;
-            i += 2;
-        }
-        let times = numbers[i].to_digit(10).unwrap() as usize;
-        (0..times).for_each(|_| result.push(current.to_string()));
+    #[test]
+    fn it_calculates_checksum_with_sequence_contiguous() {
+        let input = "2333133121414131402";
+        let expanded_sequence = expand(input).unwrap();
+        let adjusted = adjust_sequence_contiguous(expanded_sequence);
+        assert_eq!(
+            vec!["0", "0", "9", "9", "2", "1", "1", "1", "7", "7", "7", ".", "4", "4", ".", "3", "3", "3", ".", ".", ".", ".", "5", "5", "5", "5", ".", "6", "6", "6", "6", ".", ".", ".", ".", ".", "8", "8", "8", "8", ".", "."],
+            adjusted);
+        assert_eq!(2858, calculate_checksum(adjusted));
+    }
+
+    #[test]
+    fn it_solves_second_puzzle() {
+        let input = &personal_input!("input_09");
 
-        result
+        let expanded_sequence = expand(input).unwrap();
+        let adjusted = adjust_sequence_contiguous(expanded_sequence);
+        assert_eq!(6408966547049, calculate_checksum(adjusted));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Screen coordinates: x grows to the right and y grows downwards
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    pub fn step(&self, direction: Direction) -> Self {
+        *self + direction.offset()
+    }
+
+    pub fn steps(&self, direction: Direction, steps: isize) -> Self {
+        *self + direction.offset() * steps
+    }
+
+    pub fn manhattan_distance(&self, other: &Point) -> isize {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn neighbours(&self) -> [Point; 4] {
+        Direction::ALL.map(|direction| self.step(direction))
+    }
+}
+
+impl From<(isize, isize)> for Point {
+    fn from((x, y): (isize, isize)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Self::Output {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Self::Output {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: isize) -> Self::Output {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Self::Output {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+/// The four moves on a grid, read from and written as the `^>v<` arrows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting from Up
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn from_char(char: char) -> Option<Self> {
+        match char {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    pub fn rotate_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn rotate_left(self) -> Self {
+        self.rotate_right().opposite()
+    }
+
+    pub fn opposite(self) -> Self {
+        self.rotate_right().rotate_right()
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_does_vector_arithmetic() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 5);
+
+        assert_eq!(Point::new(2, 3), a + b);
+        assert_eq!(Point::new(4, -7), a - b);
+        assert_eq!(Point::new(9, -6), a * 3);
+        assert_eq!(Point::new(-3, 2), -a);
+        assert_eq!(Point::new(3, -2), Point::from((3, -2)));
+        assert_eq!(11, a.manhattan_distance(&b));
+    }
+
+    #[test]
+    fn it_steps_in_a_direction() {
+        let point = Point::new(2, 2);
+
+        assert_eq!(Point::new(2, 1), point.step(Direction::Up));
+        assert_eq!(Point::new(5, 2), point.steps(Direction::Right, 3));
+        assert_eq!(
+            [Point::new(2, 1), Point::new(3, 2), Point::new(2, 3), Point::new(1, 2)],
+            point.neighbours()
+        );
+    }
+
+    #[test]
+    fn it_rotates_directions() {
+        assert_eq!(Direction::Right, Direction::Up.rotate_right());
+        assert_eq!(Direction::Left, Direction::Up.rotate_left());
+        assert_eq!(Direction::Down, Direction::Up.opposite());
+        assert_eq!(Direction::Right, Direction::Left.opposite());
+        assert!(Direction::ALL.iter().all(|d| d.rotate_left().rotate_right() == *d));
+    }
+
+    #[test]
+    fn it_reads_directions_from_arrows() {
+        assert_eq!(
+            vec![Some(Direction::Up), Some(Direction::Down), Some(Direction::Left), Some(Direction::Right), None],
+            "^v<>x".chars().map(Direction::from_char).collect::<Vec<_>>()
+        );
+        assert_eq!("^>v<", Direction::ALL.map(Direction::to_char).iter().collect::<String>());
+    }
+}
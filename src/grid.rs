@@ -0,0 +1,235 @@
+use crate::geometry::{Direction, Point};
+use crate::parse_error::{lines, ParseError};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A rectangular grid of cells addressed by (x, y), (0, 0) is the top left corner
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self { cells: vec![value; width * height], width, height }
+    }
+
+    /// Every row must be as long as the first one, `expected` describes the cells rejected by the mapper
+    pub fn parse(day: u8, input: &str, expected: &str, mut mapper: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for line in lines(day, input) {
+            let mut row_width = 0;
+            for (x, char) in line.text.chars().enumerate() {
+                if width.is_some_and(|width| x >= width) {
+                    return Err(ParseError::new(day, line.number, x + 1, format!("a row of {} cells", width.unwrap())));
+                }
+                cells.push(mapper(char).ok_or_else(|| ParseError::new(day, line.number, x + 1, expected))?);
+                row_width += 1;
+            }
+
+            match width {
+                Some(width) if row_width < width => return Err(line.error_at_end(format!("a row of {} cells", width))),
+                _ => width = Some(row_width),
+            }
+            height += 1;
+        }
+
+        Ok(Self { cells, width: width.unwrap_or(0), height })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, Point { x, y }: Point) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    pub fn get(&self, position: Point) -> Option<&T> {
+        self.index_of(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Point) -> Option<&mut T> {
+        self.index_of(position).map(|index| &mut self.cells[index])
+    }
+
+    /// Returns false when the position is outside of the grid
+    pub fn set(&mut self, position: Point, value: T) -> bool {
+        match self.get_mut(position) {
+            Some(cell) => {
+                *cell = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.height).flat_map(|y| (0..self.width).map(move |x| Point::new(x as isize, y as isize)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn neighbours(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL.into_iter()
+            .map(move |direction| position.step(direction))
+            .filter(|&neighbour| self.contains(neighbour))
+    }
+
+    pub fn neighbours_with_diagonals(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL.into_iter()
+            .flat_map(|direction| [direction.offset(), direction.offset() + direction.rotate_right().offset()])
+            .map(move |offset| position + offset)
+            .filter(|&neighbour| self.contains(neighbour))
+    }
+
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.find_all(value).next()
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, cell)| *cell == value)
+            .map(|(position, _)| position)
+    }
+
+    pub fn map<U>(&self, mapper: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(mapper).collect(), width: self.width, height: self.height }
+    }
+
+    pub fn render(&self, mut renderer: impl FnMut(Point, &T) -> char) -> String {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let position = Point::new(x as isize, y as isize);
+                        renderer(position, &self[position])
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn index_of(&self, position: Point) -> Option<usize> {
+        if !self.contains(position) {
+            return None;
+        }
+
+        Some(position.y as usize * self.width + position.x as usize)
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Point) -> &Self::Output {
+        self.get(position)
+            .unwrap_or_else(|| panic!("{} is outside of a {}x{} grid", position, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, position: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("{} is outside of a {}x{} grid", position, width, height))
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(|_, &cell| cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+    #..
+    .S#
+    E.."};
+
+    #[test]
+    fn it_parses_and_renders_a_grid() {
+        let grid = Grid::parse(0, EXAMPLE, "a tile", Some).unwrap();
+
+        assert_eq!((3, 3), (grid.width(), grid.height()));
+        assert_eq!(Some(&'S'), grid.get(Point::new(1, 1)));
+        assert_eq!(EXAMPLE, grid.to_string());
+        assert_eq!("X..\n..X\n...", grid.map(|&c| c == '#').render(|_, &wall| if wall { 'X' } else { '.' }));
+    }
+
+    #[test]
+    fn it_reports_invalid_cells_and_ragged_rows() {
+        let tile = |c| matches!(c, '#' | '.').then_some(c);
+
+        assert_eq!(Some(ParseError::new(0, 2, 2, "'#' or '.'")), Grid::parse(0, "#..\n.S#", "'#' or '.'", tile).err());
+        assert_eq!(Some(ParseError::new(0, 2, 3, "a row of 3 cells")), Grid::parse(0, "#..\n.#", "", tile).err());
+        assert_eq!(Some(ParseError::new(0, 2, 4, "a row of 3 cells")), Grid::parse(0, "#..\n.#..", "", tile).err());
+    }
+
+    #[test]
+    fn it_checks_the_bounds() {
+        let mut grid = Grid::new(2, 3, 0);
+
+        assert!(grid.set(Point::new(1, 2), 7));
+        assert!(!grid.set(Point::new(2, 0), 7));
+        assert_eq!(Some(&7), grid.get(Point::new(1, 2)));
+        assert_eq!(None, grid.get(Point::new(-1, 0)));
+        assert_eq!(None, grid.get(Point::new(0, 3)));
+        assert_eq!(7, grid[Point::new(1, 2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "3,0 is outside of a 2x3 grid")]
+    fn it_panics_when_indexing_outside_of_the_grid() {
+        let grid = Grid::new(2, 3, 0);
+        let _ = grid[Point::new(3, 0)];
+    }
+
+    #[test]
+    fn it_lists_neighbours_inside_the_grid() {
+        let grid = Grid::parse(0, EXAMPLE, "", Some).unwrap();
+
+        assert_eq!(vec![Point::new(1, 0), Point::new(0, 1)], grid.neighbours(Point::new(0, 0)).collect::<Vec<_>>());
+        assert_eq!(4, grid.neighbours(Point::new(1, 1)).count());
+        assert_eq!(
+            vec![Point::new(1, 0), Point::new(1, 1), Point::new(0, 1)],
+            grid.neighbours_with_diagonals(Point::new(0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(8, grid.neighbours_with_diagonals(Point::new(1, 1)).count());
+    }
+
+    #[test]
+    fn it_finds_cells() {
+        let grid = Grid::parse(0, EXAMPLE, "", Some).unwrap();
+
+        assert_eq!(vec![Point::new(0, 0), Point::new(2, 1)], grid.find_all(&'#').collect::<Vec<_>>());
+        assert_eq!(Some(Point::new(0, 2)), grid.find(&'E'));
+        assert_eq!(None, grid.find(&'x'));
+    }
+}
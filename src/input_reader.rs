@@ -1,20 +1,156 @@
+use flate2::read::GzDecoder;
+use std::env;
+use std::error::Error;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+pub const INPUT_DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+
+const GZIP_MAGIC_NUMBER: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Named(String),
+    Path(PathBuf),
+    Stdin,
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    NotFound { name: String, searched: Vec<PathBuf> },
+    Unreadable { location: String, error: io::Error },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::NotFound { name, searched } => {
+                write!(f, "input '{}' not found, searched:", name)?;
+                for location in searched {
+                    write!(f, "\n  {}", location.display())?;
+                }
+                Ok(())
+            }
+            InputError::Unreadable { location, error } => write!(f, "cannot read {}: {}", location, error),
+        }
+    }
+}
+
+impl Error for InputError {}
 
 pub fn read_lines(input: &str) -> Vec<String> {
     input.lines().map(String::from).collect()
 }
 
-pub fn read_input_file(filename: &str) -> String {
-    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+pub fn input_path(filename: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("inputs")
-        .join(filename);
-    fs::read_to_string(&path).unwrap()
+        .join(filename)
+}
+
+pub fn read_input_file(filename: &str) -> String {
+    read_input(&InputSource::Named(filename.to_string()))
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Personal puzzle inputs are not committed, tests using them are skipped when the file is missing
+pub fn personal_input(filename: &str) -> Option<String> {
+    match read_input(&InputSource::Named(filename.to_string())) {
+        Ok(input) => Some(input),
+        Err(InputError::NotFound { .. }) => {
+            eprintln!("skipped: {} not present", filename);
+            None
+        }
+        Err(error) => panic!("{}", error),
+    }
+}
+
+#[macro_export]
+macro_rules! personal_input {
+    ($filename:expr) => {
+        match $crate::input_reader::personal_input($filename) {
+            Some(input) => input,
+            None => return,
+        }
+    };
+}
+
+pub fn read_input(source: &InputSource) -> Result<String, InputError> {
+    match source {
+        InputSource::Named(name) => read_path(&find_input(name)?),
+        InputSource::Path(path) => read_path(path),
+        InputSource::Stdin => {
+            let mut buffer = vec![];
+            io::stdin()
+                .read_to_end(&mut buffer)
+                .map_err(|error| InputError::Unreadable { location: "stdin".to_string(), error })?;
+            decode(buffer).map_err(|error| InputError::Unreadable { location: "stdin".to_string(), error })
+        }
+    }
+}
+
+pub fn find_input(filename: &str) -> Result<PathBuf, InputError> {
+    let searched = search_locations(filename);
+
+    searched.iter()
+        .find(|path| path.is_file())
+        .cloned()
+        .ok_or(InputError::NotFound { name: filename.to_string(), searched })
+}
+
+// AOC_INPUT_DIR wins over the inputs folder of the repository, a gzip copy is accepted next to each plain file
+pub fn search_locations(filename: &str) -> Vec<PathBuf> {
+    let input_dir = env::var_os(INPUT_DIR_VARIABLE).map(PathBuf::from);
+    search_locations_in(input_dir.as_deref(), filename)
+}
+
+fn search_locations_in(input_dir: Option<&Path>, filename: &str) -> Vec<PathBuf> {
+    input_dir
+        .map(|dir| dir.join(filename))
+        .into_iter()
+        .chain([input_path(filename)])
+        .flat_map(|path| [path.clone(), gzip_path(&path)])
+        .collect()
+}
+
+fn gzip_path(path: &Path) -> PathBuf {
+    let mut gzip = path.as_os_str().to_owned();
+    gzip.push(".gz");
+    PathBuf::from(gzip)
+}
+
+pub fn read_path(path: &Path) -> Result<String, InputError> {
+    fs::read(path)
+        .and_then(decode)
+        .map_err(|error| InputError::Unreadable { location: path.display().to_string(), error })
+}
+
+fn decode(bytes: Vec<u8>) -> io::Result<String> {
+    let text = if bytes.starts_with(&GZIP_MAGIC_NUMBER) {
+        let mut text = String::new();
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut text)?;
+        text
+    } else {
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    };
+
+    Ok(normalise(&text))
+}
+
+pub fn normalise(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .trim_end_matches(['\n', '\r'])
+        .to_string()
 }
 
 #[cfg(test)]
 mod tests {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
     use indoc::indoc;
+    use std::io::Write;
     use super::*;
 
     #[test]
@@ -30,7 +166,57 @@ mod tests {
         foo bar
         fizz
         bazz"};
-        let lines = read_lines(&input);
+        let lines = read_lines(input);
         assert_eq!(vec!["foo bar", "fizz", "bazz"], lines);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_skips_missing_personal_inputs() {
+        assert_eq!(None, personal_input("input_that_does_not_exist"));
+        assert_eq!(Some("some text\nhere and\nthere".to_string()), personal_input("test.txt"));
+    }
+
+    #[test]
+    fn it_normalises_line_endings() {
+        assert_eq!("foo bar\nfizz", normalise("foo bar\r\nfizz\r\n\r\n"));
+        assert_eq!("foo bar\n\nfizz", normalise("foo bar\n\nfizz\n"));
+    }
+
+    #[test]
+    fn it_reads_gzip_compressed_files() {
+        let path = env::temp_dir().join(format!("aoc_input_reader_{}.gz", std::process::id()));
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(b"some text\r\nthere\r\n").unwrap();
+        fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let input = read_input(&InputSource::Path(path.clone()));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!("some text\nthere", input.unwrap());
+    }
+
+    #[test]
+    fn it_searches_the_override_directory_first() {
+        let locations = search_locations_in(Some(Path::new("/data/aoc")), "input_07");
+
+        assert_eq!(
+            vec![
+                PathBuf::from("/data/aoc/input_07"),
+                PathBuf::from("/data/aoc/input_07.gz"),
+                input_path("input_07"),
+                input_path("input_07.gz"),
+            ],
+            locations
+        );
+    }
+
+    #[test]
+    fn it_lists_the_searched_locations_when_the_input_is_missing() {
+        let error = find_input("input_that_does_not_exist").unwrap_err();
+        let message = error.to_string();
+
+        assert!(message.starts_with("input 'input_that_does_not_exist' not found, searched:"));
+        assert!(message.contains(&input_path("input_that_does_not_exist").display().to_string()));
+        assert!(message.contains(&input_path("input_that_does_not_exist.gz").display().to_string()));
+    }
+}
@@ -1,26 +1,384 @@
+use advent_of_code_2024::answers::{load_answers, verify, Outcome, ANSWERS_FILE};
+use advent_of_code_2024::bench::{bench_puzzle, compare, from_csv, from_json, to_csv, to_json, BenchOptions};
+use advent_of_code_2024::input_reader::{read_input, InputError, InputSource};
+use advent_of_code_2024::solver::{find_puzzle, Answer, PUZZLES};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
 
-mod input_reader;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
+const USAGE: &str = "\
+Usage: advent_of_code_2024 <command>
+
+Commands:
+  run <day> [--part 1|2] [--input <path>|-]   solve a day (both parts by default)
+  verify [--answers <path>] [--day <day>]     check every day against the expected answers
+  bench [--day <day>] [options]               time parse, part 1 and part 2 of every day
+  help                                        print this message
+
+When --input is omitted the day's file is looked up in $AOC_INPUT_DIR and then in inputs/,
+a gzip compressed copy (<file>.gz) is accepted as well. `-` reads stdin.
+When --answers is omitted answers.toml is looked up in the same places.
+
+Bench options:
+  --warmup <n>          untimed runs before measuring (default 1)
+  --iterations <n>      timed runs per stage (default 5)
+  --json <path>         write the report as JSON
+  --csv <path>          write the report as CSV
+  --baseline <path>     compare with a previous JSON or CSV report
+  --threshold <percent> slowdown reported as a regression (default 10)";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match execute(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn execute(args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        Some("verify") => verify_answers(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command '{}'\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct RunOptions {
+    day: u8,
+    parts: Vec<u8>,
+    input: Option<String>,
+}
+
+fn parse_run_options(args: &[String]) -> Result<RunOptions, String> {
+    let mut day = None;
+    let mut parts = vec![1, 2];
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" | "-p" => {
+                let part = args.next().ok_or("--part expects a value")?;
+                parts = match part.as_str() {
+                    "1" => vec![1],
+                    "2" => vec![2],
+                    _ => return Err(format!("invalid part '{}', expected 1 or 2", part)),
+                };
+            }
+            "--input" | "-i" => {
+                input = Some(args.next().ok_or("--input expects a path or -")?.clone());
+            }
+            _ if day.is_none() => {
+                day = Some(parse_day(arg)?);
+            }
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let day = day.ok_or("missing <day>")?;
+    Ok(RunOptions { day, parts, input })
+}
+
+fn parse_day(raw_day: &str) -> Result<u8, String> {
+    match raw_day.parse::<u8>() {
+        Ok(day @ 1..=25) => Ok(day),
+        _ => Err(format!("invalid day '{}', expected a number between 1 and 25", raw_day)),
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let options = parse_run_options(args)?;
+    let puzzle = find_puzzle(options.day)
+        .ok_or(format!("day {} is not solved yet", options.day))?;
+    let input = read_input(&input_source(puzzle.input, options.input.as_deref()))
+        .map_err(|e| e.to_string())?;
+
+    println!("Day {}: {}", puzzle.day, puzzle.title);
+
+    let start = Instant::now();
+    let parsed_input = puzzle.solver.parse(&input).map_err(|e| e.to_string())?;
+    println!("Parsed input ({:.2?})", start.elapsed());
+
+    for part in options.parts {
+        if !puzzle.has_part(part) {
+            println!("Part {}: skipped, day {} has no part {}", part, puzzle.day, part);
+            continue;
+        }
+
+        let start = Instant::now();
+        let answer = puzzle.solve(parsed_input.as_ref(), part);
+        match answer {
+            Answer::Unsolved => return Err(format!("day {} part {} is not solved yet", puzzle.day, part)),
+            Answer::Error(error) => return Err(format!("day {} part {}: {}", puzzle.day, part, error)),
+            _ => {}
+        }
+        println!("Part {}: {} ({:.2?})", part, answer, start.elapsed());
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct VerifyOptions {
+    answers: Option<String>,
+    day: Option<u8>,
+}
+
+fn parse_verify_options(args: &[String]) -> Result<VerifyOptions, String> {
+    let mut answers = None;
+    let mut day = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--answers" | "-a" => {
+                answers = Some(args.next().ok_or("--answers expects a path")?.clone());
+            }
+            "--day" | "-d" => {
+                day = Some(parse_day(args.next().ok_or("--day expects a value")?)?);
+            }
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(VerifyOptions { answers, day })
+}
+
+fn verify_answers(args: &[String]) -> Result<(), String> {
+    let options = parse_verify_options(args)?;
+    let answers = load_answers(&input_source(ANSWERS_FILE, options.answers.as_deref()))
+        .map_err(|e| e.to_string())?;
+
+    let puzzles = PUZZLES.iter().filter(|p| options.day.is_none_or(|day| p.day == day));
+    let verifications = verify(puzzles, &answers);
+    if verifications.is_empty() {
+        return Err("no expected answers for the selected days".to_string());
+    }
+
+    let mut failures = 0;
+    for verification in &verifications {
+        let result = match &verification.outcome {
+            Outcome::Correct => "ok".to_string(),
+            Outcome::MissingInput => "skipped, input not present".to_string(),
+            Outcome::Wrong { expected, actual } => {
+                failures += 1;
+                format!("wrong, expected {} but got {}", expected, actual)
+            }
+            Outcome::InvalidInput(error) => {
+                failures += 1;
+                format!("failed, {}", error)
+            }
+        };
+        println!("Day {} part {}: {}", verification.day, verification.part, result);
+    }
+
+    match failures {
+        0 => Ok(()),
+        _ => Err(format!("{} of {} answers did not match", failures, verifications.len())),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct BenchCommandOptions {
+    day: Option<u8>,
+    bench: BenchOptions,
+    json: Option<String>,
+    csv: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+}
+
+fn parse_bench_options(args: &[String]) -> Result<BenchCommandOptions, String> {
+    let mut options = BenchCommandOptions {
+        day: None,
+        bench: BenchOptions::default(),
+        json: None,
+        csv: None,
+        baseline: None,
+        threshold: 10.0,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} expects a value", arg));
+        match arg.as_str() {
+            "--day" | "-d" => options.day = Some(parse_day(value()?)?),
+            "--warmup" => options.bench.warmup = parse_number(value()?)?,
+            "--iterations" => options.bench.iterations = parse_number(value()?)?,
+            "--json" => options.json = Some(value()?.clone()),
+            "--csv" => options.csv = Some(value()?.clone()),
+            "--baseline" => options.baseline = Some(value()?.clone()),
+            "--threshold" => options.threshold = parse_number(value()?)?,
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    if options.bench.iterations == 0 {
+        return Err("--iterations must be at least 1".to_string());
+    }
+    if !(options.threshold >= 0.0 && options.threshold.is_finite()) {
+        return Err(format!("invalid threshold '{}', expected a percentage of 0 or more", options.threshold));
+    }
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(raw: &str) -> Result<T, String> {
+    raw.parse::<T>().map_err(|_| format!("invalid number '{}'", raw))
+}
+
+fn bench(args: &[String]) -> Result<(), String> {
+    let options = parse_bench_options(args)?;
+
+    let puzzles: Vec<_> = PUZZLES.iter().filter(|p| options.day.is_none_or(|day| p.day == day)).collect();
+    if puzzles.is_empty() {
+        return Err(format!("day {} is not solved yet", options.day.unwrap_or_default()));
+    }
+
+    let mut timings = vec![];
+    for puzzle in puzzles {
+        let input = match read_input(&InputSource::Named(puzzle.input.to_string())) {
+            Ok(input) => input,
+            Err(InputError::NotFound { .. }) => {
+                println!("Day {}: skipped, {} not present", puzzle.day, puzzle.input);
+                continue;
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+
+        for timing in bench_puzzle(puzzle, &input, &options.bench).map_err(|e| e.to_string())? {
+            println!(
+                "Day {} {}: mean {:.2?}, median {:.2?}, min {:.2?}, max {:.2?}",
+                timing.day,
+                timing.stage,
+                timing.mean(),
+                std::time::Duration::from_nanos(timing.median_ns),
+                std::time::Duration::from_nanos(timing.min_ns),
+                std::time::Duration::from_nanos(timing.max_ns),
+            );
+            timings.push(timing);
+        }
+    }
+
+    if timings.is_empty() {
+        return Err("nothing to bench, no input is present for the selected days".to_string());
+    }
+
+    if let Some(path) = &options.json {
+        fs::write(path, to_json(&timings)).map_err(|e| format!("cannot write {}: {}", path, e))?;
+    }
+    if let Some(path) = &options.csv {
+        fs::write(path, to_csv(&timings)).map_err(|e| format!("cannot write {}: {}", path, e))?;
+    }
+
+    let Some(path) = &options.baseline else {
+        return Ok(());
+    };
+    let report = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let baseline = if path.ends_with(".csv") { from_csv(&report) } else { from_json(&report) }
+        .map_err(|e| format!("invalid baseline {}: {}", path, e))?;
+
+    let comparisons = compare(&timings, &baseline, options.threshold / 100.0);
+    for c in &comparisons {
+        println!(
+            "Day {} {}: {:.2?} -> {:.2?} ({:+.1}%){}",
+            c.day,
+            c.stage,
+            c.baseline,
+            c.current,
+            c.change * 100.0,
+            if c.regression { " REGRESSION" } else { "" }
+        );
+    }
+
+    match comparisons.iter().filter(|c| c.regression).count() {
+        0 => Ok(()),
+        regressions => Err(format!("{} stages are slower than the baseline", regressions)),
+    }
+}
+
+fn input_source(default_input: &str, input: Option<&str>) -> InputSource {
+    match input {
+        Some("-") => InputSource::Stdin,
+        Some(path) => InputSource::Path(PathBuf::from(path)),
+        None => InputSource::Named(default_input.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn it_parses_run_options() {
+        assert_eq!(
+            Ok(RunOptions { day: 7, parts: vec![1, 2], input: None }),
+            parse_run_options(&args(&["7"]))
+        );
+        assert_eq!(
+            Ok(RunOptions { day: 19, parts: vec![2], input: Some("-".to_string()) }),
+            parse_run_options(&args(&["19", "--part", "2", "--input", "-"]))
+        );
+        assert!(parse_run_options(&args(&["26"])).is_err());
+        assert!(parse_run_options(&args(&["7", "--part", "3"])).is_err());
+        assert!(parse_run_options(&args(&[])).is_err());
+    }
+
+    #[test]
+    fn it_parses_verify_options() {
+        assert_eq!(Ok(VerifyOptions { answers: None, day: None }), parse_verify_options(&args(&[])));
+        assert_eq!(
+            Ok(VerifyOptions { answers: Some("mine.toml".to_string()), day: Some(4) }),
+            parse_verify_options(&args(&["--answers", "mine.toml", "--day", "4"]))
+        );
+        assert!(parse_verify_options(&args(&["--day", "0"])).is_err());
+        assert!(parse_verify_options(&args(&["4"])).is_err());
+    }
+
+    #[test]
+    fn it_parses_bench_options() {
+        let options = parse_bench_options(&args(&[
+            "--day", "16", "--warmup", "0", "--iterations", "3", "--csv", "bench.csv", "--baseline", "old.json",
+            "--threshold", "25",
+        ])).unwrap();
+
+        assert_eq!(
+            BenchCommandOptions {
+                day: Some(16),
+                bench: BenchOptions { warmup: 0, iterations: 3 },
+                json: None,
+                csv: Some("bench.csv".to_string()),
+                baseline: Some("old.json".to_string()),
+                threshold: 25.0,
+            },
+            options
+        );
+        assert!(parse_bench_options(&args(&["--iterations", "0"])).is_err());
+        assert!(parse_bench_options(&args(&["--warmup"])).is_err());
+        assert!(parse_bench_options(&args(&["--threshold", "-5"])).is_err());
+        assert!(parse_bench_options(&args(&["--threshold", "NaN"])).is_err());
+    }
+
+    #[test]
+    fn it_chooses_the_input_source() {
+        assert_eq!(InputSource::Named("input_07".to_string()), input_source("input_07", None));
+        assert_eq!(InputSource::Stdin, input_source("input_07", Some("-")));
+        assert_eq!(InputSource::Path(PathBuf::from("my/input.gz")), input_source("input_07", Some("my/input.gz")));
+    }
+}
@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, line: usize, column: usize, expected: impl Into<String>) -> Self {
+        Self { day, line, column, expected: expected.into() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}: line {}, column {}: expected {}",
+            self.day, self.line, self.column, self.expected
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+// A line of a puzzle input, it knows its position so that parse errors can point to it
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub day: u8,
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(day: u8, index: usize, text: &'a str) -> Self {
+        Self { day, number: index + 1, text }
+    }
+
+    pub fn error_at(&self, token: &str, expected: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, self.number, self.column_of(token), expected)
+    }
+
+    pub fn error_at_end(&self, expected: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, self.number, self.text.chars().count() + 1, expected)
+    }
+
+    pub fn parse<T: FromStr>(&self, token: &str, expected: &str) -> Result<T, ParseError> {
+        token.parse::<T>().map_err(|_| self.error_at(token, expected))
+    }
+
+    pub fn split_once(&self, separator: &str) -> Result<(&'a str, &'a str), ParseError> {
+        self.text
+            .split_once(separator)
+            .ok_or_else(|| self.error_at_end(format!("'{}'", separator)))
+    }
+
+    pub fn strip_prefix(&self, prefix: &str) -> Result<&'a str, ParseError> {
+        self.text
+            .strip_prefix(prefix)
+            .ok_or_else(|| ParseError::new(self.day, self.number, 1, format!("'{}'", prefix)))
+    }
+
+    fn column_of(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let position = token.as_ptr() as usize;
+
+        let offset = if position >= start && position <= start + self.text.len() {
+            position - start
+        } else {
+            self.text.find(token).unwrap_or(self.text.len())
+        };
+
+        self.text[..offset].chars().count() + 1
+    }
+}
+
+pub fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines()
+        .enumerate()
+        .map(move |(index, text)| Line::new(day, index, text))
+}
+
+pub fn empty_input(day: u8, expected: impl Into<String>) -> ParseError {
+    ParseError::new(day, 1, 1, expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_points_to_the_token_that_failed() {
+        let line = Line::new(5, 2, "47|x3");
+        let (_, right) = line.split_once("|").unwrap();
+
+        assert_eq!(
+            Err(ParseError::new(5, 3, 4, "a page number")),
+            line.parse::<usize>(right, "a page number")
+        );
+    }
+
+    #[test]
+    fn it_reports_missing_separators_at_the_end_of_the_line() {
+        let line = Line::new(1, 0, "3 4");
+
+        assert_eq!(Err(ParseError::new(1, 1, 4, "'   '")), line.split_once("   "));
+    }
+
+    #[test]
+    fn it_formats_the_error() {
+        let error = ParseError::new(13, 7, 12, "a number");
+
+        assert_eq!("day 13: line 7, column 12: expected a number", error.to_string());
+    }
+
+    #[test]
+    fn it_numbers_lines_from_one() {
+        let numbers: Vec<_> = lines(2, "a\nb\nc").map(|l| l.number).collect();
+
+        assert_eq!(vec![1, 2, 3], numbers);
+    }
+}
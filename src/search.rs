@@ -0,0 +1,311 @@
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Searches explore the graph given by a neighbour closure and stop at the first node accepted by
+/// `is_goal`, pass `|_| false` to explore everything reachable from the starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search<N: Eq + Hash> {
+    pub distances: HashMap<N, usize>,
+    pub parents: HashMap<N, N>,
+    pub goal: Option<N>,
+}
+
+impl<N: Clone + Eq + Hash> Search<N> {
+    fn new() -> Self {
+        Self { distances: HashMap::new(), parents: HashMap::new(), goal: None }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<usize> {
+        self.distances.get(node).copied()
+    }
+
+    pub fn goal_distance(&self) -> Option<usize> {
+        self.goal.as_ref().and_then(|goal| self.distance(goal))
+    }
+
+    /// From a start to `node`, both included
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        self.distances.get(node)?;
+
+        let mut path = vec![node.clone()];
+        while let Some(parent) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        search.distances.insert(start.clone(), 0);
+        queue.push_back(start);
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        let distance = search.distances[&node];
+        for next in neighbours(&node) {
+            if search.distances.contains_key(&next) {
+                continue;
+            }
+
+            search.distances.insert(next.clone(), distance + 1);
+            search.parents.insert(next.clone(), node.clone());
+            queue.push_back(next);
+        }
+    }
+
+    search
+}
+
+pub fn dijkstra<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    astar(starts, neighbours, |_| 0, is_goal)
+}
+
+/// The heuristic must never overestimate the remaining cost to a goal
+pub fn astar<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> usize,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut search = Search::new();
+    let mut queue = PriorityQueue::new();
+    for start in starts {
+        search.distances.insert(start.clone(), 0);
+        let estimate = heuristic(&start);
+        queue.push(start, Reverse(estimate));
+    }
+
+    while let Some((node, _)) = queue.pop() {
+        if is_goal(&node) {
+            search.goal = Some(node);
+            break;
+        }
+
+        let distance = search.distances[&node];
+        for (next, cost) in neighbours(&node) {
+            let next_distance = distance + cost;
+            if search.distances.get(&next).is_some_and(|&d| d <= next_distance) {
+                continue;
+            }
+
+            search.distances.insert(next.clone(), next_distance);
+            search.parents.insert(next.clone(), node.clone());
+            let estimate = next_distance + heuristic(&next);
+            queue.push_increase(next, Reverse(estimate));
+        }
+    }
+
+    search
+}
+
+/// Like a Dijkstra search but every node keeps all the predecessors it can be reached from at its
+/// optimal distance, so that every optimal path can be rebuilt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllPaths<N: Eq + Hash> {
+    pub distances: HashMap<N, usize>,
+    pub predecessors: HashMap<N, Vec<N>>,
+    pub goals: Vec<N>,
+}
+
+impl<N: Clone + Eq + Hash> AllPaths<N> {
+    pub fn distance(&self, node: &N) -> Option<usize> {
+        self.distances.get(node).copied()
+    }
+
+    pub fn goal_distance(&self) -> Option<usize> {
+        self.goals.first().and_then(|goal| self.distance(goal))
+    }
+
+    /// Every node lying on at least one optimal path from a start to one of `ends`
+    pub fn nodes_on_paths_to<'a>(&self, ends: impl IntoIterator<Item = &'a N>) -> HashSet<N>
+    where
+        N: 'a,
+    {
+        let mut nodes = HashSet::new();
+        let mut to_visit: Vec<N> = ends.into_iter()
+            .filter(|end| self.distances.contains_key(end))
+            .cloned()
+            .collect();
+
+        while let Some(node) = to_visit.pop() {
+            if !nodes.insert(node.clone()) {
+                continue;
+            }
+            to_visit.extend(self.predecessors.get(&node).into_iter().flatten().cloned());
+        }
+
+        nodes
+    }
+
+    pub fn nodes_on_goal_paths(&self) -> HashSet<N> {
+        self.nodes_on_paths_to(&self.goals)
+    }
+
+    /// Every optimal path from a start to `end`, both included
+    pub fn paths_to(&self, end: &N) -> Vec<Vec<N>> {
+        if !self.distances.contains_key(end) {
+            return vec![];
+        }
+
+        match self.predecessors.get(end) {
+            None => vec![vec![end.clone()]],
+            Some(predecessors) => predecessors.iter()
+                .flat_map(|predecessor| self.paths_to(predecessor))
+                .map(|mut path| {
+                    path.push(end.clone());
+                    path
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Keeps searching after the first goal so that every goal at the same optimal distance is found
+pub fn all_shortest_paths<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> AllPaths<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut paths = AllPaths { distances: HashMap::new(), predecessors: HashMap::new(), goals: vec![] };
+    let mut queue = PriorityQueue::new();
+    for start in starts {
+        paths.distances.insert(start.clone(), 0);
+        queue.push(start, Reverse(0));
+    }
+
+    while let Some((node, Reverse(distance))) = queue.pop() {
+        if paths.goal_distance().is_some_and(|best| distance > best) {
+            break;
+        }
+
+        if is_goal(&node) {
+            paths.goals.push(node);
+            continue;
+        }
+
+        for (next, cost) in neighbours(&node) {
+            let next_distance = distance + cost;
+            match paths.distances.get(&next) {
+                Some(&d) if d < next_distance => continue,
+                Some(&d) if d == next_distance => {
+                    paths.predecessors.entry(next).or_default().push(node.clone());
+                }
+                _ => {
+                    paths.distances.insert(next.clone(), next_distance);
+                    paths.predecessors.insert(next.clone(), vec![node.clone()]);
+                    queue.push_increase(next, Reverse(next_distance));
+                }
+            }
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 3 costs 1 + 5, 0 -> 2 -> 3 costs 4 + 2 and 0 -> 2 -> 4 -> 3 costs 4 + 1 + 2
+    fn weighted(node: &u8) -> Vec<(u8, usize)> {
+        match node {
+            0 => vec![(1, 1), (2, 4)],
+            1 => vec![(3, 5)],
+            2 => vec![(3, 2), (4, 1)],
+            4 => vec![(3, 2)],
+            _ => vec![],
+        }
+    }
+
+    fn unweighted(node: &u8) -> Vec<u8> {
+        weighted(node).into_iter().map(|(next, _)| next).collect()
+    }
+
+    #[test]
+    fn it_finds_the_fewest_steps() {
+        let search = bfs([0], unweighted, |&n| n == 3);
+
+        assert_eq!(Some(3), search.goal);
+        assert_eq!(Some(2), search.goal_distance());
+        assert_eq!(Some(vec![0, 1, 3]), search.goal_path());
+    }
+
+    #[test]
+    fn it_explores_everything_without_a_goal() {
+        let search = bfs([2], unweighted, |_| false);
+
+        assert_eq!(None, search.goal);
+        assert_eq!(HashMap::from([(2, 0), (3, 1), (4, 1)]), search.distances);
+        assert_eq!(Some(vec![2, 3]), search.path_to(&3));
+        assert_eq!(None, search.path_to(&0));
+    }
+
+    #[test]
+    fn it_finds_the_cheapest_path() {
+        let search = dijkstra([0], weighted, |&n| n == 3);
+
+        assert_eq!(Some(6), search.goal_distance());
+        assert!(matches!(search.goal_path().unwrap().as_slice(), [0, 1, 3] | [0, 2, 3]));
+        assert_eq!(None, dijkstra([1], weighted, |&n| n == 0).goal);
+    }
+
+    #[test]
+    fn it_finds_the_cheapest_path_with_a_heuristic() {
+        let heuristic = |node: &u8| if *node == 3 { 0 } else { 1 };
+        let search = astar([0], weighted, heuristic, |&n| n == 3);
+
+        assert_eq!(Some(6), search.goal_distance());
+    }
+
+    #[test]
+    fn it_keeps_every_optimal_predecessor() {
+        let paths = all_shortest_paths([0], weighted, |&n| n == 3);
+
+        assert_eq!(vec![3], paths.goals);
+        assert_eq!(Some(6), paths.goal_distance());
+        assert_eq!(HashSet::from([0, 1, 2, 3]), paths.nodes_on_goal_paths());
+
+        let mut all = paths.paths_to(&3);
+        all.sort();
+        assert_eq!(vec![vec![0, 1, 3], vec![0, 2, 3]], all);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21, day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9};
+use crate::parse_error::ParseError;
+use std::any::Any;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    Unsolved,
+    // The part cannot be solved for this input, e.g. a program that crashes or an unreachable goal
+    Error(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Unsolved => write!(f, "unsolved"),
+            Answer::Error(message) => write!(f, "error: {}", message),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        i64::try_from(value).map_or_else(|_| Answer::Text(value.to_string()), Answer::Number)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or_else(|_| Answer::Text(value.to_string()), Answer::Number)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(value: isize) -> Self {
+        Answer::Number(value as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl<T: Into<Answer>, E: fmt::Display> From<Result<T, E>> for Answer {
+    fn from(value: Result<T, E>) -> Self {
+        match value {
+            Ok(answer) => answer.into(),
+            Err(error) => Answer::Error(error.to_string()),
+        }
+    }
+}
+
+pub trait Solver {
+    type Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(&self, input: &Self::Input) -> Answer;
+
+    fn part2(&self, input: &Self::Input) -> Answer;
+
+    // False for a day that has no second puzzle, its part 2 is then never asked
+    fn has_part2(&self) -> bool {
+        true
+    }
+}
+
+// Object safe view of a `Solver`, so that solvers with different inputs can live in the same registry
+pub trait DynSolver: Sync {
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError>;
+
+    fn part1(&self, input: &dyn Any) -> Answer;
+
+    fn part2(&self, input: &dyn Any) -> Answer;
+
+    fn has_part2(&self) -> bool;
+}
+
+impl<S> DynSolver for S
+where
+    S: Solver + Sync,
+    S::Input: 'static,
+{
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+        let parsed_input = Solver::parse(self, input)?;
+        Ok(Box::new(parsed_input))
+    }
+
+    fn part1(&self, input: &dyn Any) -> Answer {
+        Solver::part1(self, downcast_input::<S>(input))
+    }
+
+    fn part2(&self, input: &dyn Any) -> Answer {
+        Solver::part2(self, downcast_input::<S>(input))
+    }
+
+    fn has_part2(&self) -> bool {
+        Solver::has_part2(self)
+    }
+}
+
+fn downcast_input<S: Solver>(input: &dyn Any) -> &S::Input
+where
+    S::Input: 'static,
+{
+    input.downcast_ref::<S::Input>()
+        .expect("the input has been parsed by a different solver")
+}
+
+pub struct Puzzle {
+    pub day: u8,
+    pub title: &'static str,
+    pub input: &'static str,
+    pub solver: &'static dyn DynSolver,
+}
+
+impl Puzzle {
+    pub fn has_part(&self, part: u8) -> bool {
+        part == 1 || (part == 2 && self.solver.has_part2())
+    }
+
+    pub fn solve(&self, parsed_input: &dyn Any, part: u8) -> Answer {
+        match part {
+            1 => self.solver.part1(parsed_input),
+            2 if self.has_part(2) => self.solver.part2(parsed_input),
+            _ => Answer::Unsolved,
+        }
+    }
+}
+
+pub static PUZZLES: [Puzzle; 25] = [
+    Puzzle { day: 1, title: "Historian Hysteria", input: "input_01", solver: &day1::Day1 },
+    Puzzle { day: 2, title: "Red-Nosed Reports", input: "input_02", solver: &day2::Day2 },
+    Puzzle { day: 3, title: "Mull It Over", input: "input_03", solver: &day3::Day3 },
+    Puzzle { day: 4, title: "Ceres Search", input: "input_04", solver: &day4::Day4 },
+    Puzzle { day: 5, title: "Print Queue", input: "input_05", solver: &day5::Day5 },
+    Puzzle { day: 6, title: "Guard Gallivant", input: "input_06", solver: &day6::Day6 },
+    Puzzle { day: 7, title: "Bridge Repair", input: "input_07", solver: &day7::Day7 },
+    Puzzle { day: 8, title: "Resonant Collinearity", input: "input_08", solver: &day8::Day8 },
+    Puzzle { day: 9, title: "Disk Fragmenter", input: "input_09", solver: &day9::Day9 },
+    Puzzle { day: 10, title: "Hoof It", input: "input_10", solver: &day10::Day10 },
+    Puzzle { day: 11, title: "Plutonian Pebbles", input: "input_11", solver: &day11::Day11 },
+    Puzzle { day: 12, title: "Garden Groups", input: "input_12", solver: &day12::Day12 },
+    Puzzle { day: 13, title: "Claw Contraption", input: "input_13", solver: &day13::Day13 },
+    Puzzle { day: 14, title: "Restroom Redoubt", input: "input_14", solver: &day14::Day14 },
+    Puzzle { day: 15, title: "Warehouse Woes", input: "input_15", solver: &day15::Day15 },
+    Puzzle { day: 16, title: "Reindeer Maze", input: "input_16", solver: &day16::Day16 },
+    Puzzle { day: 17, title: "Chronospatial Computer", input: "input_day17", solver: &day17::Day17 },
+    Puzzle { day: 18, title: "RAM Run", input: "input_18", solver: &day18::Day18 },
+    Puzzle { day: 19, title: "Linen Layout", input: "input_19", solver: &day19::Day19 },
+    Puzzle { day: 20, title: "Race Condition", input: "input_20", solver: &day20::Day20 },
+    Puzzle { day: 21, title: "Keypad Conundrum", input: "input_21", solver: &day21::Day21 },
+    Puzzle { day: 22, title: "Monkey Market", input: "input_22", solver: &day22::Day22 },
+    Puzzle { day: 23, title: "LAN Party", input: "input_23", solver: &day23::Day23 },
+    Puzzle { day: 24, title: "Crossed Wires", input: "input_24", solver: &day24::Day24 },
+    Puzzle { day: 25, title: "Code Chronicle", input: "input_25", solver: &day25::Day25 },
+];
+
+pub fn find_puzzle(day: u8) -> Option<&'static Puzzle> {
+    PUZZLES.iter().find(|p| p.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_registers_every_day_once_in_order() {
+        let days: Vec<_> = PUZZLES.iter().map(|p| p.day).collect();
+        assert_eq!((1..=25).collect::<Vec<_>>(), days);
+    }
+
+    #[test]
+    fn it_finds_a_puzzle_by_day() {
+        assert_eq!("Linen Layout", find_puzzle(19).unwrap().title);
+        assert!(find_puzzle(26).is_none());
+    }
+
+    #[test]
+    fn it_solves_through_the_registry() {
+        let puzzle = find_puzzle(1).unwrap();
+        let parsed_input = puzzle.solver.parse("3   4\n4   3\n2   5\n1   3\n3   9\n3   3").unwrap();
+
+        assert_eq!(Answer::Number(11), puzzle.solve(parsed_input.as_ref(), 1));
+        assert_eq!(Answer::Number(31), puzzle.solve(parsed_input.as_ref(), 2));
+        assert_eq!(Answer::Unsolved, puzzle.solve(parsed_input.as_ref(), 3));
+    }
+
+    #[test]
+    fn it_knows_which_parts_a_day_has() {
+        assert!(find_puzzle(24).unwrap().has_part(2));
+        assert!(find_puzzle(25).unwrap().has_part(1));
+        assert!(!find_puzzle(25).unwrap().has_part(2));
+        assert!(!find_puzzle(1).unwrap().has_part(3));
+    }
+
+    #[test]
+    fn it_reports_invalid_input() {
+        let puzzle = find_puzzle(1).unwrap();
+
+        assert_eq!(
+            Some(ParseError::new(1, 2, 5, "a location id")),
+            puzzle.solver.parse("3   4\n4   x").err()
+        );
+    }
+
+    #[test]
+    fn it_formats_answers() {
+        assert_eq!("42", Answer::from(42_usize).to_string());
+        assert_eq!("6,1", Answer::from("6,1".to_string()).to_string());
+        assert_eq!(Answer::Number(7), Answer::from(Ok::<_, String>(7_usize)));
+        assert_eq!("error: no path", Answer::from(Err::<usize, _>("no path")).to_string());
+        assert_eq!(Answer::Text("18446744073709551615".to_string()), Answer::from(u64::MAX));
+    }
+}